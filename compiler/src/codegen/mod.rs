@@ -0,0 +1,1215 @@
+pub mod ty;
+
+use std::sync::Arc;
+use std::{collections::VecDeque, ops::Deref};
+
+use faststr::FastStr;
+use fxhash::{FxHashMap, FxHashSet};
+use proc_macro2::TokenStream;
+use quote::{format_ident, ToTokens};
+
+use crate::tags::Editable;
+use crate::{
+    context::Context,
+    error::{Error, Result},
+    resolver::rir::{Graph, Node, NodeKind, Path, Type},
+    symbol::{DefId, IdentName},
+    tags::{Backoff, Concurrency, Construct, Join, Retry, Timeout},
+};
+
+pub struct Codegen {
+    cx: Context,
+    /// Nodes whose struct has been written, shared by every graph of the document.
+    emitted: FxHashSet<DefId>,
+    /// First-join nodes whose `{Node}Input` enum has been written, with its variants.
+    joined: FxHashMap<DefId, Vec<DefId>>,
+    /// The items written so far into each module, keyed by the path of the module.
+    streams: FxHashMap<Vec<FastStr>, TokenStream>,
+}
+
+/// The request, error, generic parameters and bounds of the `run` of a graph.
+struct RunSig {
+    req_ty: TokenStream,
+    error_ty: TokenStream,
+    generics: Vec<TokenStream>,
+    bounds: TokenStream,
+}
+
+/// The topology of a single graph, as seen from its entry node.
+#[derive(Default)]
+struct Layout {
+    nesteds: FxHashMap<DefId, FastStr>,
+    in_degrees: FxHashMap<DefId, u32>,
+    froms: FxHashMap<DefId, Vec<DefId>>,
+    tos: FxHashMap<DefId, Vec<DefId>>,
+}
+
+/// The name of `node` in a mermaid graph, labelled with its path when it has one, or with
+/// `use G` for a graph used as a node.
+fn mermaid_id(node: &Node) -> String {
+    mermaid_label(display_name(node))
+}
+
+/// `label` as a mermaid id, with the label given apart when it is not a plain name.
+fn mermaid_label(label: String) -> String {
+    if label.contains([' ', ':']) {
+        format!("{}[{}]", label.replace(' ', "_").replace("::", "_"), label)
+    } else {
+        label
+    }
+}
+
+/// `node` as written in the DSL, with the modules it is declared in, e.g. `recall::A`, or
+/// `use recall::G` for a graph used as a node.
+fn display_name(node: &Node) -> String {
+    let name = node.name.to_string();
+    match name.strip_prefix("use ") {
+        Some(graph) if matches!(node.kind, NodeKind::Graph(_)) => {
+            format!("use {}", path_name(&node.module, graph))
+        }
+        _ => path_name(&node.module, &name),
+    }
+}
+
+/// `name` declared in `module`, as written in the DSL.
+fn path_name(module: &Path, name: &str) -> String {
+    module
+        .segments
+        .iter()
+        .map(|s| s.to_string())
+        .chain(std::iter::once(name.to_string()))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// `node` with its modules joined by `_`, to be cased into an identifier.
+fn qualified(node: &Node) -> String {
+    display_name(node).replace("::", "_")
+}
+
+/// The path to `name`, declared in module `to`, from code in module `from`.
+fn path_from(from: &Path, to: &Path, name: &str) -> TokenStream {
+    let common = from
+        .segments
+        .iter()
+        .zip(to.segments.iter())
+        .take_while(|(a, b)| a.0 == b.0)
+        .count();
+    let supers = (common..from.segments.len()).map(|_| quote::quote!(super::));
+    let segments = to.segments[common..]
+        .iter()
+        .map(|s| format_ident!("{}", s.0.as_str()));
+    let name = format_ident!("{}", name);
+    quote::quote!(#(#supers)* #(#segments::)* #name)
+}
+
+/// The items written into module `module` followed by the modules nested in it.
+fn assemble(
+    streams: &mut FxHashMap<Vec<FastStr>, TokenStream>,
+    module: &mut Vec<FastStr>,
+) -> TokenStream {
+    let mut stream = streams.remove(module).unwrap_or_default();
+    let mut children: Vec<_> = streams
+        .keys()
+        .filter(|key| key.len() > module.len() && key.starts_with(module))
+        .map(|key| key[module.len()].clone())
+        .collect();
+    children.sort();
+    children.dedup();
+    for child in children {
+        let name = format_ident!("{}", child.as_str());
+        module.push(child);
+        let inner = assemble(streams, module);
+        module.pop();
+        stream.extend(quote::quote! {
+            pub mod #name {
+                use ::static_graph::Runnable as _;
+                #inner
+            }
+        });
+    }
+    stream
+}
+
+impl Deref for Codegen {
+    type Target = Context;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cx
+    }
+}
+
+impl Codegen {
+    pub fn new(cx: Context) -> Self {
+        Self {
+            cx,
+            emitted: FxHashSet::default(),
+            joined: FxHashMap::default(),
+            streams: FxHashMap::default(),
+        }
+    }
+
+    pub fn write_document(&mut self, def_ids: Vec<DefId>) -> Result<TokenStream> {
+        for def_id in def_ids {
+            self.write_graph(def_id)?;
+        }
+        let mut stream = quote::quote! {
+            pub use ::static_graph::Runnable;
+        };
+        stream.extend(assemble(&mut self.streams, &mut Vec::new()));
+        Ok(stream)
+    }
+
+    /// Write `tokens` into the module at `module`.
+    fn emit(&mut self, module: &Path, tokens: TokenStream) {
+        let key = module.segments.iter().map(|s| s.sym.0.clone()).collect();
+        self.streams.entry(key).or_default().extend(tokens);
+    }
+
+    /// The name of `node` in struct fields, qualified by its modules so nodes of different
+    /// modules do not collide, e.g. `recall_a` for `recall::A`.
+    fn local_name(&self, node: &Node) -> proc_macro2::Ident {
+        qualified(node).as_str().snake_ident().as_syn_ident()
+    }
+
+    /// The name `node` is bound to in the `run` of a graph, prefixed so it cannot collide
+    /// with the other locals of `run`, e.g. `node_recall_a`.
+    fn binding_name(&self, node: &Node) -> proc_macro2::Ident {
+        format_ident!("node_{}", self.local_name(node))
+    }
+
+    /// The camel-case counterpart of [`Self::local_name`], naming the enum variants and
+    /// generic parameters standing for `node`, e.g. `RecallA`.
+    fn variant_name(&self, node: &Node) -> proc_macro2::Ident {
+        qualified(node).as_str().upper_camel_ident().as_syn_ident()
+    }
+
+    /// The type of `node`, as named from code in module `from`.
+    fn node_ty(&self, from: &Path, node: &Node) -> TokenStream {
+        let name = self.upper_camel_name(&node.name);
+        path_from(from, &node.module, &name)
+    }
+
+    fn expect_graph(&self, def_id: DefId) -> Result<Arc<Graph>> {
+        self.graph(def_id)
+            .ok_or_else(|| Error::Codegen(format!("graph {def_id:?} is not defined")))
+    }
+
+    fn expect_node(&self, def_id: DefId) -> Result<Arc<Node>> {
+        self.node(def_id)
+            .ok_or_else(|| Error::Codegen(format!("node {def_id:?} is not defined")))
+    }
+
+    /// draw a mermaid graph , go [mermaid.js.org](https://mermaid.js.org) for more detail
+    /// ```mermaid
+    ///graph TD;
+    ///  subgraph G
+    ///    E --> X;
+    ///    E --> Y;
+    ///    X --> O;
+    ///    Y --> O;
+    ///    O
+    ///  end
+    /// ```
+    pub fn mermaid(&self, def_ids: &[DefId]) -> String {
+        let mut ret = String::from("graph TD;\n");
+        for def_id in def_ids.iter() {
+            if let Some(graph) = self.graph(*def_id) {
+                let mut visited = FxHashSet::default();
+                let mut bytes = format!(
+                    "subgraph {}\n",
+                    mermaid_label(path_name(&graph.module, &graph.name))
+                );
+                let mut node_queue = VecDeque::new();
+                node_queue.push_back(graph.entry_node);
+                visited.insert(graph.entry_node);
+                while !node_queue.is_empty() {
+                    let node = node_queue.pop_front().unwrap();
+                    if let Some(node) = self.node(node) {
+                        if !node.to_nodes.is_empty() {
+                            for to in node.to_nodes.iter() {
+                                if !visited.contains(to) {
+                                    node_queue.push_back(*to);
+                                    visited.insert(*to);
+                                }
+                                // Conditional edges are dotted.
+                                let arrow = match (
+                                    node.conditions.contains_key(to),
+                                    node.each_edges.contains(to),
+                                ) {
+                                    (true, _) => "-.->",
+                                    (false, true) => "-->|each|",
+                                    (false, false) => "-->",
+                                };
+                                if let Some(to) = self.node(*to) {
+                                    bytes.push_str("  ");
+                                    bytes.push_str(&mermaid_id(&node));
+                                    bytes.push_str(arrow);
+                                    bytes.push_str(&mermaid_id(&to));
+                                    bytes.push_str(";\n");
+                                }
+                            }
+                        } else {
+                            bytes.push_str("  ");
+                            bytes.push_str(&mermaid_id(&node));
+                            bytes.push('\n');
+                        }
+                    }
+                }
+
+                bytes.push_str("end\n\n");
+                ret.push_str(&bytes);
+            }
+        }
+
+        ret
+    }
+
+    pub fn write_graph(&mut self, def_id: DefId) -> Result<()> {
+        let graph = self.expect_graph(def_id)?;
+        let graph_name = self.upper_camel_name(&graph.name).as_syn_ident();
+
+        let entry_node = self.expect_node(graph.entry_node)?;
+        let entry_node_name = self.local_name(&entry_node);
+        let entry_node_ty = self.node_ty(&graph.module, &entry_node);
+
+        self.emit(
+            &graph.module,
+            quote::quote! {
+                pub struct #graph_name {
+                    pub #entry_node_name: ::std::sync::Arc<#entry_node_ty>,
+                }
+                impl #graph_name {
+                    pub fn new() -> Self {
+                        Self {
+                            #entry_node_name: ::std::sync::Arc::new(#entry_node_ty::new()),
+                        }
+                    }
+                }
+            },
+        );
+
+        self.write_node(graph.entry_node, &entry_node)?;
+
+        let layout = self.layout(&graph)?;
+        self.write_run(graph, layout)
+    }
+
+    /// Walk the graph from its entry node, recording the edges and where each node lives.
+    fn layout(&self, graph: &Graph) -> Result<Layout> {
+        let mut layout = Layout::default();
+        let mut stack = vec![(graph.entry_node, FastStr::from_static_str("self"))];
+        while let Some((def_id, nested)) = stack.pop() {
+            if layout.nesteds.contains_key(&def_id) {
+                continue;
+            }
+            let node = self.expect_node(def_id)?;
+            let nested: FastStr = format!("{}.{}", nested, self.local_name(&node)).into();
+            layout.nesteds.insert(def_id, nested.clone());
+            for did in &node.to_nodes {
+                layout
+                    .in_degrees
+                    .entry(*did)
+                    .and_modify(|e| *e += 1)
+                    .or_insert(1);
+                layout.tos.entry(def_id).or_default().push(*did);
+                layout.froms.entry(*did).or_default().push(def_id);
+            }
+            stack.extend(node.to_nodes.iter().rev().map(|did| (*did, nested.clone())));
+        }
+        Ok(layout)
+    }
+
+    pub fn write_node(&mut self, def_id: DefId, node: &Arc<Node>) -> Result<()> {
+        if !self.emitted.insert(def_id) {
+            return Ok(());
+        }
+
+        let name = self.upper_camel_name(&node.name).as_syn_ident();
+        let mut nodes = TokenStream::new();
+        let mut nodes_impl = TokenStream::new();
+        // A graph used as a node is embedded next to the nodes after it.
+        if let NodeKind::Graph(graph_id) = node.kind {
+            let graph = self.expect_graph(graph_id)?;
+            let ty = path_from(
+                &node.module,
+                &graph.module,
+                &self.upper_camel_name(&graph.name),
+            );
+            nodes.extend(quote::quote! {
+                pub graph: ::std::sync::Arc<#ty>,
+            });
+            nodes_impl.extend(quote::quote! {
+                graph: ::std::sync::Arc::new(#ty::new()),
+            });
+        }
+        for did in &node.to_nodes {
+            let to = self.expect_node(*did)?;
+            let name = self.local_name(&to);
+            let ty = self.node_ty(&node.module, &to);
+            nodes.extend(quote::quote! {
+                pub #name: ::std::sync::Arc<#ty>,
+            });
+            nodes_impl.extend(quote::quote! {
+                #name: ::std::sync::Arc::new(#ty::new()),
+            });
+        }
+
+        let mut fields = TokenStream::new();
+        let mut fields_impl = TokenStream::new();
+        for f in &node.fields {
+            let name = self.snake_name(&f.name).as_syn_ident();
+            let ty = f.ty.to_codegen_ty();
+            fields.extend(quote::quote! {
+                pub #name: #ty,
+            });
+
+            let tags = self
+                .tag(f.tag_id)
+                .ok_or_else(|| Error::Codegen(format!("tags of field `{}` are missing", f.name)))?;
+            if let Some(c) = tags.get::<Construct>() {
+                let ident: Vec<_> = c.0.split("::").map(|s| format_ident!("{}", s)).collect();
+                if let Some(Editable(true)) = tags.get::<Editable>() {
+                    fields_impl.extend(quote::quote! {
+                        #name: ::static_graph::ArcSwap::from_pointee(#(#ident)::*()),
+                    });
+                } else {
+                    fields_impl.extend(quote::quote! {
+                        #name: #(#ident)::*(),
+                    });
+                }
+            } else {
+                fields_impl.extend(quote::quote! {
+                    #name: ::std::default::Default::default(),
+                });
+            };
+        }
+
+        self.emit(
+            &node.module,
+            quote::quote! {
+                pub struct #name {
+                    #nodes
+                    #fields
+                }
+                impl #name {
+                    pub fn new() -> Self {
+                        Self {
+                            #nodes_impl
+                            #fields_impl
+                        }
+                    }
+                }
+            },
+        );
+
+        if node.kind == NodeKind::Switch {
+            self.write_switch_enums(node)?;
+        }
+
+        for did in &node.to_nodes {
+            self.write_node(*did, &self.expect_node(*did)?)?;
+        }
+        Ok(())
+    }
+
+    /// `{Switch}Route`, the response of a switch picking a branch, and `{Merge}Input`, what
+    /// the merge node receives from whichever branch ran.
+    fn write_switch_enums(&mut self, switch: &Node) -> Result<()> {
+        let route = format_ident!("{}Route", self.upper_camel_name(&switch.name).as_str());
+        let mut variants = Vec::with_capacity(switch.to_nodes.len());
+        let mut resps = Vec::with_capacity(switch.to_nodes.len());
+        let mut merge = None;
+        for did in &switch.to_nodes {
+            let branch = self.expect_node(*did)?;
+            let variant = self.variant_name(&branch);
+            resps.push(format_ident!("{}Resp", variant));
+            variants.push(variant);
+            merge = branch.to_nodes.first().copied();
+        }
+        let merge = self.expect_node(merge.ok_or_else(|| {
+            Error::Codegen(format!("switch `{}` has no merge node", switch.name))
+        })?)?;
+        let input = format_ident!("{}Input", self.upper_camel_name(&merge.name).as_str());
+
+        self.emit(
+            &switch.module,
+            quote::quote! {
+                #[derive(Debug, Clone)]
+                pub enum #route<T> {
+                    #(#variants(T),)*
+                }
+                impl<T> ::static_graph::Route for #route<T> {
+                    type Payload = T;
+                }
+            },
+        );
+        self.emit(
+            &merge.module,
+            quote::quote! {
+                #[derive(Debug, Clone)]
+                pub enum #input<#(#resps),*> {
+                    #(#variants(#resps),)*
+                }
+            },
+        );
+        Ok(())
+    }
+
+    fn write_run(&mut self, graph: Arc<Graph>, mut layout: Layout) -> Result<()> {
+        let name = self.upper_camel_name(&graph.name).as_syn_ident();
+        let mut queue = VecDeque::new();
+
+        if layout.in_degrees.contains_key(&graph.entry_node) {
+            return Err(Error::Codegen(format!(
+                "entry node of graph `{}` has predecessors",
+                graph.name
+            )));
+        }
+
+        queue.push_back(graph.entry_node);
+        let mut bodys = TokenStream::new();
+        let mut out_resp = None;
+
+        // Types left out of the DSL become generic parameters of `run`, unless the request
+        // type is declared: bounds on concrete types are resolved through the node impls, so
+        // the missing types are named through the impls as well.
+        let entry_node = self.expect_node(graph.entry_node)?;
+        let entry_ty = self.node_ty(&graph.module, &entry_node);
+        let mut generics = Vec::new();
+        let req_ty = match &graph.req_ty {
+            Some(ty) => ty.to_codegen_ty().into_token_stream(),
+            None => {
+                generics.push(quote::quote!(Req));
+                quote::quote!(Req)
+            }
+        };
+        let error_ty = match (&graph.error_ty, &graph.req_ty) {
+            (Some(ty), _) => ty.to_codegen_ty().into_token_stream(),
+            (None, Some(_)) => {
+                quote::quote!(<#entry_ty as ::static_graph::Runnable<#req_ty, ()>>::Error)
+            }
+            (None, None) => quote::quote!(Error),
+        };
+        let mut sig = RunSig {
+            req_ty: req_ty.clone(),
+            error_ty: error_ty.clone(),
+            generics,
+            bounds: TokenStream::new(),
+        };
+        let mut resp_tys: FxHashMap<DefId, TokenStream> = FxHashMap::default();
+        // Merge nodes of switches, which receive the output of the switch's task.
+        let mut merged: FxHashMap<DefId, DefId> = FxHashMap::default();
+        while !queue.is_empty() {
+            let sz = queue.len();
+            for _ in 0..sz {
+                let mut channels = TokenStream::new();
+
+                let did = queue.pop_front().unwrap();
+                let node = self.expect_node(did)?;
+                let name = self.binding_name(&node);
+                let req = format_ident!("{}_req", name);
+
+                let from_dids = match merged.get(&did) {
+                    Some(switch_id) => vec![*switch_id],
+                    None => layout.froms.get(&did).cloned().unwrap_or_default(),
+                };
+                let mut upper_prev_resps = Vec::new();
+                let mut resps = Vec::new();
+                let mut conditions = Vec::new();
+                let mut rxs = Vec::with_capacity(from_dids.len());
+                let mut receives = Vec::with_capacity(from_dids.len());
+                let mut each = None;
+                for from_did in &from_dids {
+                    let node = self.expect_node(*from_did)?;
+
+                    let f_name = self.binding_name(&node);
+                    let mut upper_prev_resp = resp_tys[from_did].clone();
+                    let rx = format_ident!("{}_rx_{}", name, f_name);
+                    // A soft predecessor hands over its failure instead of failing this node.
+                    if node.soft_edges.contains(&did) {
+                        upper_prev_resp = quote::quote! {
+                            ::std::result::Result<#upper_prev_resp, ::static_graph::GraphError<#error_ty>>
+                        };
+                        receives.push(quote::quote!(async {
+                            Ok::<_, ::static_graph::GraphError<#error_ty>>(
+                                #rx.recv().await.unwrap_or_else(|e| Err(::static_graph::GraphError::Recv(e)))
+                            )
+                        }));
+                    } else {
+                        receives.push(quote::quote!(async {
+                            #rx.recv().await.unwrap_or_else(|e| Err(::static_graph::GraphError::Recv(e)))
+                        }));
+                    }
+
+                    let resp = format_ident!("{}_resp", f_name);
+                    if let Some(condition) = node.conditions.get(&did) {
+                        let condition = condition.segments.iter().map(|s| format_ident!("{}", s));
+                        conditions.push(quote::quote!(#(#condition)::*(&#req, &#resp)));
+                    }
+
+                    if node.each_edges.contains(&did) {
+                        each = Some(resp.clone());
+                    }
+                    resps.push(resp.clone());
+                    rxs.push(rx);
+
+                    upper_prev_resps.push(upper_prev_resp);
+                }
+
+                let join = self
+                    .tag(node.tag_id)
+                    .and_then(|tags| tags.get::<Join>().copied())
+                    .unwrap_or(Join::All);
+                match join {
+                    // The first failed predecessor fails this node without running it.
+                    Join::All if !resps.is_empty() => {
+                        channels.extend(quote::quote! {
+                            let (#(#resps,)*) = static_graph::try_join!(#(#receives),*)?;
+                        });
+                    }
+                    Join::All => {}
+                    // The first predecessor to succeed runs this node; it only fails once all
+                    // predecessors failed.
+                    Join::First => {
+                        let input = self.write_join_input(&graph, did, &node, &from_dids)?;
+                        let variants: Vec<_> = from_dids
+                            .iter()
+                            .map(|did| Ok(self.variant_name(&*self.expect_node(*did)?)))
+                            .collect::<Result<_>>()?;
+                        let done: Vec<_> =
+                            rxs.iter().map(|rx| format_ident!("{}_done", rx)).collect();
+                        let aborts = self.raced_predecessors(&layout, &from_dids)?;
+                        let resp = format_ident!("{}_input", name);
+                        channels.extend(quote::quote! {
+                            #(let mut #done = false;)*
+                            let mut last = None;
+                            let #resp = loop {
+                                static_graph::select! {
+                                    #(resp = #rxs.recv(), if !#done => {
+                                        #done = true;
+                                        match resp {
+                                            Ok(Ok(resp)) => break #input::#variants(resp),
+                                            Ok(Err(e)) => last = Some(e),
+                                            Err(e) => last = Some(::static_graph::GraphError::Recv(e)),
+                                        }
+                                    })*
+                                    else => return Err(last.unwrap()),
+                                }
+                            };
+                            #(#aborts.abort();)*
+                        });
+                        upper_prev_resps = vec![quote::quote!(#input<#(#upper_prev_resps),*>)];
+                        resps = vec![resp];
+                    }
+                    // Runs with the first `k` predecessors to succeed; the rest are `None`.
+                    Join::Quorum(k) => {
+                        let done: Vec<_> =
+                            rxs.iter().map(|rx| format_ident!("{}_done", rx)).collect();
+                        let aborts = self.raced_predecessors(&layout, &from_dids)?;
+                        let tolerated = from_dids.len().saturating_sub(k);
+                        channels.extend(quote::quote! {
+                            #(let mut #done = false;)*
+                            #(let mut #resps = None;)*
+                            let mut succeeded = 0usize;
+                            let mut failed = 0usize;
+                            let mut last = None;
+                            while succeeded < #k {
+                                static_graph::select! {
+                                    #(resp = #rxs.recv(), if !#done => {
+                                        #done = true;
+                                        match resp {
+                                            Ok(Ok(resp)) => {
+                                                #resps = Some(resp);
+                                                succeeded += 1;
+                                            }
+                                            Ok(Err(e)) => {
+                                                failed += 1;
+                                                last = Some(e);
+                                            }
+                                            Err(e) => {
+                                                failed += 1;
+                                                last = Some(::static_graph::GraphError::Recv(e));
+                                            }
+                                        }
+                                    })*
+                                    else => {}
+                                }
+                                if failed > #tolerated {
+                                    return Err(last.unwrap());
+                                }
+                            }
+                            #(#aborts.abort();)*
+                        });
+                        upper_prev_resps = upper_prev_resps
+                            .into_iter()
+                            .map(|resp| quote::quote!(::std::option::Option<#resp>))
+                            .collect();
+                    }
+                }
+
+                let declared_resp_ty = match &node.resp_ty {
+                    Some(ty) => Some(ty),
+                    None if !layout.tos.contains_key(&did) => graph.resp_ty.as_ref(),
+                    None => None,
+                };
+                // A node run for each item receives one item at a time.
+                let prev = if each.is_some() {
+                    let item =
+                        quote::quote!(<#(#upper_prev_resps)* as ::std::iter::IntoIterator>::Item);
+                    sig.bounds.extend(quote::quote! {
+                        #(#upper_prev_resps)*: ::std::iter::IntoIterator,
+                        #item: Clone + Send + Sync + 'static,
+                    });
+                    item
+                } else {
+                    quote::quote!((#(#upper_prev_resps),*))
+                };
+                let upper_resp =
+                    self.bind_resp(&mut sig, &graph, did, &node, prev, declared_resp_ty);
+
+                let tx = format_ident!("{}_tx", name);
+                let nested: Vec<_> = layout
+                    .nesteds
+                    .get(&did)
+                    .ok_or_else(|| {
+                        Error::Codegen(format!("node `{}` is not reachable", node.name))
+                    })?
+                    .split('.')
+                    .map(|s| format_ident!("{}", s))
+                    .collect();
+
+                if let Some(to_dids) = layout.tos.get(&did).cloned() {
+                    let mut successors = Vec::with_capacity(to_dids.len());
+                    let (run, visible_resp) = if node.kind == NodeKind::Switch {
+                        let (merge_id, run, input) = self.write_switch_run(
+                            &mut sig,
+                            &graph,
+                            &node,
+                            &upper_resp,
+                            &req,
+                            &resps,
+                            &mut resp_tys,
+                        )?;
+                        for to_did in &to_dids {
+                            layout.in_degrees.remove(to_did);
+                        }
+                        if let Some(in_degree) = layout.in_degrees.get_mut(&merge_id) {
+                            *in_degree -= to_dids.len() as u32;
+                            if *in_degree == 0 {
+                                layout.in_degrees.remove(&merge_id);
+                                queue.push_back(merge_id);
+                            }
+                        }
+                        merged.insert(merge_id, did);
+                        successors.push(merge_id);
+                        (run, input)
+                    } else {
+                        for to_did in &to_dids {
+                            if let Some(in_degree) = layout.in_degrees.get_mut(to_did) {
+                                *in_degree -= 1;
+                                if *in_degree == 0 {
+                                    layout.in_degrees.remove(to_did);
+                                    queue.push_back(*to_did);
+                                }
+                            }
+                        }
+                        successors.extend(to_dids);
+                        match &each {
+                            Some(items) => (
+                                self.write_each_run(&node, &name, &req, items)?,
+                                quote::quote!(::std::vec::Vec<#upper_resp>),
+                            ),
+                            None => (
+                                self.write_node_run(
+                                    &node,
+                                    name.to_token_stream(),
+                                    req.to_token_stream(),
+                                    &resps,
+                                )?,
+                                upper_resp,
+                            ),
+                        }
+                    };
+
+                    // Successors of a node behind a conditional edge see `None` when it was skipped.
+                    let run = if conditions.is_empty() {
+                        resp_tys.insert(did, visible_resp);
+                        run
+                    } else {
+                        resp_tys.insert(did, quote::quote!(::std::option::Option<#visible_resp>));
+                        quote::quote! {
+                            if #(#conditions)&&* {
+                                (#run).map(Some)
+                            } else {
+                                Ok(None)
+                            }
+                        }
+                    };
+
+                    let mut rxs = Vec::with_capacity(successors.len());
+                    for to_did in &successors {
+                        let node = self.expect_node(*to_did)?;
+                        let to_name = self.binding_name(&node);
+                        rxs.push(format_ident!("{}_rx_{}", to_name, name));
+                    }
+                    let len = successors.len() + 1;
+                    // A node raced by its only consumer can be aborted once it lost.
+                    let raced = match successors.as_slice() {
+                        [to_did] => {
+                            self.tag(self.expect_node(*to_did)?.tag_id)
+                                .is_some_and(|tags| {
+                                    matches!(
+                                        tags.get::<Join>(),
+                                        Some(Join::First | Join::Quorum(_))
+                                    )
+                                })
+                        }
+                        _ => false,
+                    };
+                    let abort = raced.then(|| {
+                        let abort = format_ident!("{}_abort", name);
+                        quote::quote!(let #abort =)
+                    });
+                    bodys.extend(quote::quote! {
+                        let #req = req.clone();
+                        let #name = #(#nested.)*clone();
+                        let (#tx, _) = static_graph::sync::broadcast::channel(#len);
+                        #(let mut #rxs = #tx.subscribe();)*
+                        #abort tasks.spawn(async move {
+                            let resp = async {
+                                #channels
+                                #run
+                            }.await;
+                            #tx.send(resp).ok();
+                        });
+                    });
+                } else {
+                    if out_resp.is_some() {
+                        return Err(Error::Codegen(format!(
+                            "graph `{}` has more than one sink node",
+                            graph.name
+                        )));
+                    }
+
+                    if !conditions.is_empty() {
+                        return Err(Error::Codegen(format!(
+                            "sink node `{}` of graph `{}` is behind a conditional edge",
+                            node.name, graph.name
+                        )));
+                    }
+
+                    if each.is_some() {
+                        return Err(Error::Codegen(format!(
+                            "sink node `{}` of graph `{}` runs for each item",
+                            node.name, graph.name
+                        )));
+                    }
+
+                    out_resp.replace(upper_resp);
+                    let run = self.write_node_run(
+                        &node,
+                        quote::quote!(#(#nested).*),
+                        quote::quote!(req),
+                        &resps,
+                    )?;
+                    bodys.extend(quote::quote! {
+                        #channels
+                        #run
+                    });
+                }
+            }
+        }
+
+        if !layout.in_degrees.is_empty() {
+            return Err(Error::Codegen(format!(
+                "graph `{}` contains a cycle",
+                graph.name
+            )));
+        }
+
+        let out_resp = out_resp
+            .ok_or_else(|| Error::Codegen(format!("graph `{}` has no sink node", graph.name)))?;
+        let RunSig {
+            mut generics,
+            bounds,
+            ..
+        } = sig;
+        if graph.error_ty.is_none() && graph.req_ty.is_none() {
+            generics.push(quote::quote!(Error));
+        }
+        let generics = if generics.is_empty() {
+            TokenStream::new()
+        } else {
+            quote::quote!(<#(#generics),*>)
+        };
+        self.emit(&graph.module, quote::quote! {
+            impl #name {
+                pub async fn run #generics (&self, req: #req_ty) -> ::std::result::Result<#out_resp, ::static_graph::GraphError<#error_ty>>
+                where
+                    #req_ty: Clone + Send + Sync + 'static,
+                    #error_ty: Clone + Send + Sync + 'static,
+                    #bounds
+                {
+                    // Dropping the set aborts every node still running, so a run that fails
+                    // or is cancelled does not leave its tasks behind.
+                    let mut tasks = static_graph::task::JoinSet::<()>::new();
+                    #bodys
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Bound `node` to run after responses of type `prev` and return the type of its response,
+    /// or of the payload it hands to the chosen branch for a switch.
+    fn bind_resp(
+        &self,
+        sig: &mut RunSig,
+        graph: &Graph,
+        did: DefId,
+        node: &Node,
+        prev: TokenStream,
+        declared_resp_ty: Option<&Type>,
+    ) -> TokenStream {
+        let RunSig {
+            req_ty,
+            error_ty,
+            generics,
+            bounds,
+        } = sig;
+        // A graph used as a node runs on the request alone and declares its output.
+        if let (NodeKind::Graph(_), Some(ty)) = (node.kind, declared_resp_ty) {
+            let ty = ty.to_codegen_ty().into_token_stream();
+            bounds.extend(quote::quote!(#ty: Clone + Send + Sync + 'static,));
+            return ty;
+        }
+        let upper_name = self.node_ty(&graph.module, node);
+        let runnable = quote::quote!(::static_graph::Runnable<#req_ty, #prev>);
+        let route = (node.kind == NodeKind::Switch).then(|| {
+            let route = format!("{}Route", self.upper_camel_name(&node.name));
+            path_from(&graph.module, &node.module, &route)
+        });
+        let wrap = |ty: &TokenStream| match &route {
+            Some(route) => quote::quote!(#route<#ty>),
+            None => ty.clone(),
+        };
+
+        // A bound may not name its own associated types, so projected ones are left out.
+        let mut assoc_tys = Vec::new();
+        let upper_resp = match declared_resp_ty {
+            Some(ty) => {
+                let ty = ty.to_codegen_ty().into_token_stream();
+                let resp = wrap(&ty);
+                assoc_tys.push(quote::quote!(Resp = #resp));
+                ty
+            }
+            None if graph.req_ty.is_some() => match &route {
+                Some(_) => {
+                    quote::quote!(<<#upper_name as #runnable>::Resp as ::static_graph::Route>::Payload)
+                }
+                None => quote::quote!(<#upper_name as #runnable>::Resp),
+            },
+            None => {
+                let suffix = if route.is_some() { "Payload" } else { "Resp" };
+                let generic =
+                    format_ident!("{}{}", self.variant_name(node), suffix).into_token_stream();
+                let resp = wrap(&generic);
+                assoc_tys.push(quote::quote!(Resp = #resp));
+                generics.push(generic.clone());
+                generic
+            }
+        };
+        if graph.error_ty.is_some() || graph.req_ty.is_none() || did != graph.entry_node {
+            assoc_tys.push(quote::quote!(Error = #error_ty));
+        }
+        bounds.extend(quote::quote! {
+            #upper_name: ::static_graph::Runnable<#req_ty, #prev, #(#assoc_tys),*>,
+            #upper_resp: Clone + Send + Sync + 'static,
+        });
+        upper_resp
+    }
+
+    /// The expression running `node` once per item of `items`, at most `concurrency` at a
+    /// time, and collecting the responses in the order of the items.
+    fn write_each_run(
+        &self,
+        node: &Node,
+        name: &proc_macro2::Ident,
+        req: &proc_macro2::Ident,
+        items: &proc_macro2::Ident,
+    ) -> Result<TokenStream> {
+        let tags = self
+            .tag(node.tag_id)
+            .ok_or_else(|| Error::Codegen(format!("tags of node `{}` are missing", node.name)))?;
+        let item = format_ident!("item");
+        let run = self.write_node_run(
+            node,
+            name.to_token_stream(),
+            req.to_token_stream(),
+            std::slice::from_ref(&item),
+        )?;
+        let (semaphore, share, permit) = match tags.get::<Concurrency>() {
+            Some(Concurrency(limit)) => (
+                quote::quote! {
+                    let semaphore = ::std::sync::Arc::new(static_graph::sync::Semaphore::new(#limit));
+                },
+                quote::quote!(let semaphore = semaphore.clone();),
+                quote::quote!(let _permit = semaphore.acquire_owned().await;),
+            ),
+            None => Default::default(),
+        };
+
+        // The items run on a set of their own, so they are aborted along with this node.
+        Ok(quote::quote! {
+            {
+                #semaphore
+                let mut items = static_graph::task::JoinSet::new();
+                let mut len = 0;
+                for (index, #item) in ::std::iter::IntoIterator::into_iter(#items).enumerate() {
+                    let #name = #name.clone();
+                    let #req = #req.clone();
+                    #share
+                    items.spawn(async move {
+                        #permit
+                        (index, #run)
+                    });
+                    len += 1;
+                }
+                let mut resps: ::std::vec::Vec<_> = (0..len).map(|_| None).collect();
+                while let Some(joined) = items.join_next().await {
+                    match joined {
+                        Ok((index, resp)) => resps[index] = Some(resp?),
+                        Err(e) => ::std::panic::resume_unwind(e.into_panic()),
+                    }
+                }
+                Ok(resps.into_iter().map(Option::unwrap).collect())
+            }
+        })
+    }
+
+    /// The abort handles of the predecessors a partially joined node is the only consumer of.
+    fn raced_predecessors(
+        &self,
+        layout: &Layout,
+        from_dids: &[DefId],
+    ) -> Result<Vec<proc_macro2::Ident>> {
+        from_dids
+            .iter()
+            .filter(|from_did| layout.tos[*from_did].len() == 1)
+            .map(|from_did| {
+                let node = self.expect_node(*from_did)?;
+                Ok(format_ident!("{}_abort", self.binding_name(&node)))
+            })
+            .collect()
+    }
+
+    /// Write the `{Node}Input` enum a first-join node receives, with one variant per
+    /// predecessor, and return its name.
+    fn write_join_input(
+        &mut self,
+        graph: &Graph,
+        did: DefId,
+        node: &Node,
+        from_dids: &[DefId],
+    ) -> Result<TokenStream> {
+        let name = format!("{}Input", self.upper_camel_name(&node.name));
+        let input = format_ident!("{}", name);
+        let path = path_from(&graph.module, &node.module, &name);
+        match self.joined.get(&did) {
+            Some(written) if written == from_dids => return Ok(path),
+            Some(_) => {
+                return Err(Error::Codegen(format!(
+                    "node `{}` joins different predecessors in different graphs",
+                    node.name
+                )))
+            }
+            None => {}
+        }
+        self.joined.insert(did, from_dids.to_vec());
+
+        let mut variants = Vec::with_capacity(from_dids.len());
+        let mut resps = Vec::with_capacity(from_dids.len());
+        for from_did in from_dids {
+            let from = self.expect_node(*from_did)?;
+            let variant = self.variant_name(&from);
+            resps.push(format_ident!("{}Resp", variant));
+            variants.push(variant);
+        }
+        self.emit(
+            &node.module,
+            quote::quote! {
+                #[derive(Debug, Clone)]
+                pub enum #input<#(#resps),*> {
+                    #(#variants(#resps),)*
+                }
+            },
+        );
+        Ok(path)
+    }
+
+    /// The expression running `switch` and then the branch its response picks, all within the
+    /// switch's task, along with the merge node and the type of the `{Merge}Input` it produces.
+    #[allow(clippy::too_many_arguments)]
+    fn write_switch_run(
+        &self,
+        sig: &mut RunSig,
+        graph: &Graph,
+        switch: &Node,
+        payload_ty: &TokenStream,
+        req: &proc_macro2::Ident,
+        resps: &[proc_macro2::Ident],
+        resp_tys: &mut FxHashMap<DefId, TokenStream>,
+    ) -> Result<(DefId, TokenStream, TokenStream)> {
+        let name = self.binding_name(switch);
+        let route = path_from(
+            &graph.module,
+            &switch.module,
+            &format!("{}Route", self.upper_camel_name(&switch.name)),
+        );
+        let merge_id = self
+            .expect_node(switch.to_nodes[0])?
+            .to_nodes
+            .first()
+            .copied()
+            .ok_or_else(|| Error::Codegen(format!("switch `{}` has no merge node", switch.name)))?;
+        let merge = self.expect_node(merge_id)?;
+        let input = path_from(
+            &graph.module,
+            &merge.module,
+            &format!("{}Input", self.upper_camel_name(&merge.name)),
+        );
+
+        let payload = format_ident!("payload");
+        let mut arms = TokenStream::new();
+        let mut branch_resps = Vec::with_capacity(switch.to_nodes.len());
+        for branch_id in &switch.to_nodes {
+            let branch = self.expect_node(*branch_id)?;
+            let variant = self.variant_name(&branch);
+            let branch_name = self.local_name(&branch);
+            let resp = self.bind_resp(
+                sig,
+                graph,
+                *branch_id,
+                &branch,
+                payload_ty.clone(),
+                branch.resp_ty.as_ref(),
+            );
+            resp_tys.insert(*branch_id, resp.clone());
+            branch_resps.push(resp);
+
+            let run = self.write_node_run(
+                &branch,
+                quote::quote!(#name.#branch_name),
+                req.to_token_stream(),
+                std::slice::from_ref(&payload),
+            )?;
+            arms.extend(quote::quote! {
+                #route::#variant(#payload) => (#run).map(#input::#variant),
+            });
+        }
+
+        let run = self.write_node_run(
+            switch,
+            name.to_token_stream(),
+            quote::quote!(#req.clone()),
+            resps,
+        )?;
+        Ok((
+            merge_id,
+            quote::quote! {
+                match (#run)? {
+                    #arms
+                }
+            },
+            quote::quote!(#input<#(#branch_resps),*>),
+        ))
+    }
+
+    /// The expression running `node` through `target` once its predecessors responded, with
+    /// the node's timeout and retry annotations applied.
+    fn write_node_run(
+        &self,
+        node: &Node,
+        target: TokenStream,
+        req: TokenStream,
+        resps: &[proc_macro2::Ident],
+    ) -> Result<TokenStream> {
+        let tags = self
+            .tag(node.tag_id)
+            .ok_or_else(|| Error::Codegen(format!("tags of node `{}` are missing", node.name)))?;
+        let node_name = display_name(node);
+
+        // Every attempt but the last needs its own copy of the inputs. A graph used as a node
+        // only takes the request, and its errors are already graph errors.
+        let retry = tags.get::<Retry>().map(|r| r.0).filter(|r| *r > 0);
+        let req = if retry.is_some() {
+            quote::quote!(#req.clone())
+        } else {
+            req
+        };
+        let (run, map_err) = match node.kind {
+            NodeKind::Graph(_) => (quote::quote!(#target.graph.run(#req)), TokenStream::new()),
+            _ if retry.is_some() => (
+                quote::quote!(#target.run(#req, (#(#resps.clone()),*))),
+                quote::quote!(.map_err(::static_graph::GraphError::Node)),
+            ),
+            _ => (
+                quote::quote!(#target.run(#req, (#(#resps),*))),
+                quote::quote!(.map_err(::static_graph::GraphError::Node)),
+            ),
+        };
+
+        let attempt = match tags.get::<Timeout>() {
+            Some(Timeout(timeout)) => {
+                let millis = timeout.as_millis() as u64;
+                quote::quote! {
+                    match static_graph::time::timeout(::std::time::Duration::from_millis(#millis), #run).await {
+                        Ok(resp) => resp #map_err,
+                        Err(_) => Err(::static_graph::GraphError::Timeout { node: #node_name }),
+                    }
+                }
+            }
+            None => quote::quote!(#run.await #map_err),
+        };
+        // The responses of its predecessors only tell a graph used as a node when to run.
+        let attempt = match node.kind {
+            NodeKind::Graph(_) if !resps.is_empty() => quote::quote! {
+                {
+                    let _ = (#(&#resps),*);
+                    #attempt
+                }
+            },
+            _ => attempt,
+        };
+
+        let Some(retry) = retry else {
+            return Ok(attempt);
+        };
+        let backoff = tags.get::<Backoff>().map_or(0, |b| b.0.as_millis() as u64);
+        Ok(quote::quote! {
+            {
+                let mut attempts = 0u32;
+                let mut backoff = ::std::time::Duration::from_millis(#backoff);
+                loop {
+                    attempts += 1;
+                    match #attempt {
+                        Ok(resp) => break Ok(resp),
+                        Err(last) if attempts > #retry => {
+                            break Err(::static_graph::GraphError::Exhausted {
+                                node: #node_name,
+                                attempts,
+                                last: ::std::boxed::Box::new(last),
+                            })
+                        }
+                        Err(_) => {
+                            static_graph::time::sleep(backoff).await;
+                            backoff = backoff.saturating_mul(2);
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
@@ -64,6 +64,16 @@ impl Context {
         self.tags.get(&tag_id).cloned()
     }
 
+    /// Tags from the annotations written before `node`.
+    pub fn node_tags(&self, node_id: DefId) -> Option<Arc<Tags>> {
+        self.tag(self.nodes.get(&node_id)?.tag_id)
+    }
+
+    /// Tags from the annotations written before `graph`.
+    pub fn graph_tags(&self, graph_id: DefId) -> Option<Arc<Tags>> {
+        self.tag(self.graphs.get(&graph_id)?.tag_id)
+    }
+
     pub fn snake_name(&self, ident: &Ident) -> FastStr {
         (&***ident).snake_ident()
     }
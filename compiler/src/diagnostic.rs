@@ -0,0 +1,156 @@
+use std::fmt;
+
+use crate::parser::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Error => f.write_str("error"),
+            Level::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// A message about a span of a graph description, not yet tied to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub level: Level,
+    pub message: String,
+    pub span: Span,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            level: Level::Error,
+            message: message.into(),
+            span,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            level: Level::Warning,
+            ..Self::error(message, span)
+        }
+    }
+
+    #[must_use]
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Resolve the span against `src`, the contents of `file`.
+    pub fn report(&self, file: &str, src: &str) -> Report {
+        let (line, column) = line_column(src, self.span.start);
+        let line_start = src[..self.span.start.min(src.len())]
+            .rfind('\n')
+            .map_or(0, |pos| pos + 1);
+        let source_line = src[line_start..]
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let end = self.span.end.min(line_start + source_line.len());
+        let width = src
+            .get(self.span.start..end)
+            .map_or(0, |s| s.chars().count())
+            .max(1);
+
+        Report {
+            level: self.level,
+            message: self.message.clone(),
+            file: file.to_string(),
+            line,
+            column,
+            source_line,
+            width,
+            span: self.span,
+            notes: self.notes.clone(),
+        }
+    }
+}
+
+/// A [`Diagnostic`] located in a file, printed the way rustc prints its errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub level: Level,
+    pub message: String,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub source_line: String,
+    pub width: usize,
+    /// The span of the diagnostic within `file`.
+    pub span: Span,
+    pub notes: Vec<String>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        let indent: String = self
+            .source_line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        writeln!(f, "{}: {}", self.level, self.message)?;
+        writeln!(f, "{gutter}--> {}:{}:{}", self.file, self.line, self.column)?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{gutter} | {indent}{}", "^".repeat(self.width))?;
+        for note in &self.notes {
+            write!(f, "\n{gutter} = note: {note}")?;
+        }
+        Ok(())
+    }
+}
+
+/// 1-based line and column of byte `offset` in `src`.
+pub fn line_column(src: &str, offset: usize) -> (usize, usize) {
+    let consumed = &src[..offset.min(src.len())];
+    let line = consumed.matches('\n').count() + 1;
+    let column = match consumed.rfind('\n') {
+        Some(pos) => consumed[pos + 1..].chars().count() + 1,
+        None => consumed.chars().count() + 1,
+    };
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_column() {
+        let input = "node A {\n}\ngraph";
+        assert_eq!(line_column(input, 0), (1, 1));
+        assert_eq!(line_column(input, 5), (1, 6));
+        assert_eq!(line_column(input, 11), (3, 1));
+    }
+
+    #[test]
+    fn test_report() {
+        let src = "node A {}\nnode X - O {}\n";
+        let report = Diagnostic::error("expected `->` or `{`", Span::new(17, 17))
+            .report("example.graph", src);
+        assert_eq!(
+            report.to_string(),
+            "error: expected `->` or `{`\n \
+             --> example.graph:2:8\n  \
+             |\n\
+             2 | node X - O {}\n  \
+             |        ^"
+        );
+    }
+}
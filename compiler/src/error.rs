@@ -0,0 +1,55 @@
+use std::fmt;
+
+use crate::diagnostic::Report;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors produced while compiling a graph description into Rust code.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading the graph description or writing the generated files failed.
+    Io(std::io::Error),
+    /// The graph description is not valid DSL.
+    Parse(Box<Report>),
+    /// The graph description parsed but does not describe valid graphs.
+    Resolve(Vec<Report>),
+    /// The resolved graph cannot be turned into code.
+    Codegen(String),
+    /// Formatting the generated code failed.
+    Format(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {e}"),
+            Error::Parse(report) => write!(f, "{report}"),
+            Error::Resolve(reports) => {
+                for (i, report) in reports.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f, "\n")?;
+                    }
+                    write!(f, "{report}")?;
+                }
+                Ok(())
+            }
+            Error::Codegen(msg) => write!(f, "codegen error: {msg}"),
+            Error::Format(msg) => write!(f, "format error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,467 @@
+//! The compiler behind `static-graph`: it parses graph descriptions, resolves them and
+//! generates the code running them. Use it through `static-graph`, from `build.rs` or the
+//! `graph!` macro.
+
+pub mod codegen;
+pub mod context;
+pub mod diagnostic;
+pub mod error;
+pub mod index;
+pub mod parser;
+pub mod resolver;
+pub mod source;
+pub mod symbol;
+pub mod tags;
+
+pub use crate::error::{Error, Result};
+
+use crate::{
+    codegen::Codegen,
+    context::Context,
+    diagnostic::Report,
+    parser::document::Document,
+    resolver::{ResolveResult, Resolver},
+    source::SourceMap,
+    symbol::DefId,
+};
+
+use proc_macro2::TokenStream;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// Generate the code of the graphs described by `src`, without reading or writing any file.
+///
+/// `src` cannot import other files, and warnings found in it are dropped.
+pub fn compile_str(src: &str) -> Result<TokenStream> {
+    let (document, sources) = source::load_str("<input>", src)?;
+    let (mut cg, entrys, _) = resolve(document, &sources)?;
+    cg.write_document(entrys)
+}
+
+/// Resolve `document`, read from `sources`, into a code generator for its graphs, along with
+/// the graphs to generate and the warnings found.
+fn resolve(document: Document, sources: &SourceMap) -> Result<(Codegen, Vec<DefId>, Vec<Report>)> {
+    let ResolveResult {
+        graphs,
+        nodes,
+        fields,
+        tags,
+        entrys,
+        warnings,
+    } = Resolver::default()
+        .resolve_document(document)
+        .map_err(|errors| Error::Resolve(errors.iter().map(|d| sources.report(d)).collect()))?;
+
+    let mut cx = Context::new();
+    cx.set_graphs(graphs);
+    cx.set_nodes(nodes);
+    cx.set_fields(fields);
+    cx.set_tags(tags);
+
+    let warnings = warnings.iter().map(|w| sources.report(w)).collect();
+    Ok((Codegen::new(cx), entrys, warnings))
+}
+
+#[must_use]
+pub fn configure() -> Builder {
+    Builder {
+        emit_rerun_if_changed: std::env::var_os("CARGO").is_some(),
+        out_dir: None,
+        file_name: "gen_graph.rs".into(),
+        enable_mermaid: false,
+        merge_files: false,
+        formatter: Formatter::default(),
+        rustfmt: std::env::var_os("RUSTFMT").map_or_else(|| "rustfmt".into(), PathBuf::from),
+    }
+}
+
+/// How the generated code is formatted before it is written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Formatter {
+    /// Pretty-print the code in process with `prettyplease`.
+    #[default]
+    Prettyplease,
+    /// Run `rustfmt`, or the binary [`Builder::rustfmt`] or `RUSTFMT` names. When it is not installed, the code is
+    /// pretty-printed with `prettyplease` instead, with a warning printed for cargo when
+    /// writing to `OUT_DIR` and returned by [`Builder::compile_to_string`]. Any other
+    /// failure of `rustfmt` is an [`Error::Format`].
+    Rustfmt,
+    /// Leave the code on a single line, as generated.
+    None,
+}
+
+/// The code generated by [`Builder::compile_to_string`], with the warnings found in the
+/// graph files.
+#[derive(Debug, Clone)]
+pub struct Compiled {
+    pub code: String,
+    pub warnings: Vec<Report>,
+    /// Why the code was not formatted with the configured [`Formatter`], when it was
+    /// formatted otherwise.
+    pub format_warning: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Builder {
+    emit_rerun_if_changed: bool,
+    out_dir: Option<PathBuf>,
+    file_name: PathBuf,
+    enable_mermaid: bool, // generate mermaid file
+    merge_files: bool,
+    formatter: Formatter,
+    rustfmt: PathBuf,
+}
+
+impl Builder {
+    #[must_use]
+    pub fn out_dir(mut self, out_dir: impl AsRef<Path>) -> Self {
+        self.out_dir = Some(out_dir.as_ref().to_path_buf());
+        self
+    }
+
+    #[must_use]
+    pub fn file_name(mut self, file_name: impl AsRef<Path>) -> Self {
+        self.file_name = file_name.as_ref().to_path_buf();
+        self
+    }
+
+    #[must_use]
+    pub fn emit_rerun_if_changed(mut self, enable: bool) -> Self {
+        self.emit_rerun_if_changed = enable;
+        self
+    }
+
+    #[must_use]
+    pub fn enable_mermaid(mut self, enable: bool) -> Self {
+        self.enable_mermaid = enable;
+        self
+    }
+
+    /// Generate the graphs of every file compiled by [`Builder::compile_all`] into one
+    /// namespace, instead of a module named after each file.
+    #[must_use]
+    pub fn merge_files(mut self, enable: bool) -> Self {
+        self.merge_files = enable;
+        self
+    }
+
+    #[must_use]
+    pub fn formatter(mut self, formatter: Formatter) -> Self {
+        self.formatter = formatter;
+        self
+    }
+
+    /// The rustfmt binary [`Formatter::Rustfmt`] runs, by default the one `RUSTFMT` names or
+    /// else `rustfmt`.
+    #[must_use]
+    pub fn rustfmt(mut self, bin: impl AsRef<Path>) -> Self {
+        self.rustfmt = bin.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn compile(self, graph: impl AsRef<Path>) -> Result<()> {
+        let out_dir = self.resolve_out_dir()?;
+        let (cg, entrys, warnings) = self.load(&[graph.as_ref().to_path_buf()], false, true)?;
+        for warning in &warnings {
+            self.warn(&warning.to_string());
+        }
+        self.write(&out_dir, cg, entrys)
+    }
+
+    /// Generate the graphs of several files, resolved together, into a single file.
+    ///
+    /// A directory stands for the `.graph` files in it, and a file name may be a pattern of
+    /// `*` and `?` wildcards. The items of each file are generated into a module named after
+    /// the file, and refer to those of another file through its module, unless
+    /// [`Builder::merge_files`] puts them all in one namespace. Files they import are
+    /// generated once, outside of the modules.
+    pub fn compile_all(self, graphs: impl IntoIterator<Item = impl AsRef<Path>>) -> Result<()> {
+        let out_dir = self.resolve_out_dir()?;
+        let mut paths = Vec::new();
+        for graph in graphs {
+            let graph = graph.as_ref();
+            let files = source::graph_files(graph)?;
+            // New files in a directory are picked up on the next build.
+            if self.emit_rerun_if_changed && files.first().map(PathBuf::as_path) != Some(graph) {
+                let dir = if graph.is_dir() {
+                    graph
+                } else {
+                    graph.parent().unwrap_or(Path::new("."))
+                };
+                println!("cargo:rerun-if-changed={}", dir.display());
+            }
+            paths.extend(files);
+        }
+        let (cg, entrys, warnings) = self.load(&paths, !self.merge_files, true)?;
+        for warning in &warnings {
+            self.warn(&warning.to_string());
+        }
+        self.write(&out_dir, cg, entrys)
+    }
+
+    /// Generate the code of the graphs described by `graph` and the files it imports, and
+    /// return it formatted instead of writing it to `OUT_DIR`. Nothing is printed for cargo:
+    /// the warnings found are returned with the code.
+    pub fn compile_to_string(self, graph: impl AsRef<Path>) -> Result<Compiled> {
+        let (mut cg, entrys, warnings) =
+            self.load(&[graph.as_ref().to_path_buf()], false, false)?;
+        let (code, format_warning) =
+            format(self.formatter, &self.rustfmt, cg.write_document(entrys)?)?;
+        Ok(Compiled {
+            code,
+            warnings,
+            format_warning,
+        })
+    }
+
+    fn resolve_out_dir(&self) -> Result<PathBuf> {
+        if let Some(out_dir) = self.out_dir.as_ref() {
+            return Ok(out_dir.clone());
+        }
+        Ok(std::env::var_os("OUT_DIR")
+            .map(PathBuf::from)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "`OUT_DIR` is not set, configure one with `Builder::out_dir`",
+                )
+            })?)
+    }
+
+    /// Write the code of `entrys`, and their mermaid graph when enabled, to `out_dir`.
+    fn write(self, out_dir: &Path, mut cg: Codegen, entrys: Vec<DefId>) -> Result<()> {
+        if self.enable_mermaid {
+            let ret = cg.mermaid(&entrys);
+            let mut name = self
+                .file_name
+                .file_stem()
+                .unwrap_or_default()
+                .to_os_string();
+            name.push(".mermaid");
+            let out = out_dir.join(name);
+            let mut file = std::io::BufWriter::new(std::fs::File::create(&out)?);
+            file.write_all(ret.trim().as_bytes())?;
+            file.flush()?;
+        }
+        let stream = cg.write_document(entrys)?;
+        let code = if self.file_name.extension().is_none_or(|ext| ext == "rs") {
+            let (code, warning) = format(self.formatter, &self.rustfmt, stream)?;
+            if let Some(warning) = warning {
+                self.warn(&warning);
+            }
+            code
+        } else {
+            stream.to_string()
+        };
+        let out = out_dir.join(self.file_name);
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&out)?);
+        file.write_all(code.as_bytes())?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Read and resolve `graphs`, each in a module of its own with `modules`, along with the
+    /// warnings found. Cargo is told about the files read when `rerun` is set too.
+    fn load(
+        &self,
+        graphs: &[PathBuf],
+        modules: bool,
+        rerun: bool,
+    ) -> Result<(Codegen, Vec<DefId>, Vec<Report>)> {
+        let rerun = rerun && self.emit_rerun_if_changed;
+        if rerun {
+            for graph in graphs {
+                println!("cargo:rerun-if-changed={}", graph.display());
+            }
+        }
+
+        let (document, sources) = source::load_all(graphs, modules)?;
+        if rerun {
+            for file in sources.files() {
+                if !graphs.contains(&file.path) {
+                    println!("cargo:rerun-if-changed={}", file.path.display());
+                }
+            }
+        }
+
+        resolve(document, &sources)
+    }
+
+    /// Print `warning` for cargo when writing to `OUT_DIR` from a build script.
+    fn warn(&self, warning: &str) {
+        if self.out_dir.is_some() {
+            return;
+        }
+        for line in warning.lines() {
+            println!("cargo:warning={line}");
+        }
+    }
+}
+
+/// Format `stream` as Rust source with `formatter`, running the rustfmt binary at `bin` for
+/// [`Formatter::Rustfmt`], along with a warning when `formatter` could not be used and the
+/// code was formatted otherwise.
+fn format(
+    formatter: Formatter,
+    bin: &Path,
+    stream: TokenStream,
+) -> Result<(String, Option<String>)> {
+    match formatter {
+        Formatter::Prettyplease => Ok((pretty_print(stream)?, None)),
+        Formatter::Rustfmt => match rustfmt(bin, &stream.to_string()) {
+            Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                let warning = format!("cannot run rustfmt, formatting with prettyplease: {e}");
+                Ok((pretty_print(stream)?, Some(warning)))
+            }
+            formatted => Ok((formatted?, None)),
+        },
+        Formatter::None => Ok((stream.to_string(), None)),
+    }
+}
+
+fn pretty_print(stream: TokenStream) -> Result<String> {
+    let file = syn::parse2::<syn::File>(stream).map_err(|e| Error::Format(e.to_string()))?;
+    Ok(prettyplease::unparse(&file))
+}
+
+/// Format `code` with the rustfmt binary at `bin`. Not finding it is an [`Error::Io`] of kind
+/// `NotFound`, and any other failure an [`Error::Format`].
+fn rustfmt(bin: &Path, code: &str) -> Result<String> {
+    let failed = |e: std::io::Error| Error::Format(format!("cannot run rustfmt: {e}"));
+    let mut child = Command::new(bin)
+        .arg("--edition")
+        .arg("2021")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Error::Io(e),
+            _ => failed(e),
+        })?;
+
+    // Written from another thread, as rustfmt may fill its output pipe before reading all of
+    // its input.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let code = code.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(code.as_bytes()));
+    let output = child.wait_with_output().map_err(failed)?;
+    let written = writer.join().expect("writing to rustfmt panicked");
+    // A rustfmt failing before it read all of its input breaks the pipe, so its own error
+    // is the one worth reporting.
+    if !output.status.success() {
+        return Err(Error::Format(
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+    written.map_err(failed)?;
+    String::from_utf8(output.stdout).map_err(|e| Error::Format(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{compile_str, configure, format, Error, Formatter};
+
+    #[test]
+    fn test_compile_str() {
+        let stream = compile_str("node E -> O {}\nnode O {}\ngraph G(E)").unwrap();
+        let code = stream.to_string();
+        assert!(code.contains("pub struct G"));
+        assert!(code.contains("pub struct E"));
+
+        match compile_str("node E {}\ngraph G(Missing)") {
+            Err(Error::Resolve(reports)) => {
+                assert_eq!(reports[0].message, "cannot find node `Missing`");
+                assert_eq!((reports[0].file.as_str(), reports[0].line), ("<input>", 2));
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_compile_to_string() {
+        let dir = std::env::temp_dir().join(format!("static-graph-lib-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let graph = dir.join("a.graph");
+        std::fs::write(&graph, "node E -> O {}\nnode O {}\nnode X {}\ngraph G(E)").unwrap();
+
+        let compiled = configure().compile_to_string(&graph).unwrap();
+        assert!(compiled.code.contains("\npub struct G {\n"));
+        let warnings: Vec<_> = compiled.warnings.iter().map(|w| &*w.message).collect();
+        assert_eq!(warnings, ["node `X` is not reachable from any graph"]);
+        assert_eq!(compiled.warnings[0].line, 3);
+        assert!(compiled.format_warning.is_none());
+
+        let compiled = configure()
+            .formatter(Formatter::Rustfmt)
+            .rustfmt("/nonexistent/rustfmt")
+            .compile_to_string(&graph)
+            .unwrap();
+        assert!(compiled
+            .format_warning
+            .unwrap()
+            .starts_with("cannot run rustfmt"));
+    }
+
+    #[test]
+    fn test_format() {
+        let stream = compile_str("node E -> O {}\nnode O {}\ngraph G(E)").unwrap();
+        let (code, warning) = format(
+            Formatter::Prettyplease,
+            Path::new("rustfmt"),
+            stream.clone(),
+        )
+        .unwrap();
+        assert!(code.contains("\npub struct G {\n"));
+        assert!(warning.is_none());
+        assert_eq!(
+            format(Formatter::None, Path::new("rustfmt"), stream.clone()).unwrap(),
+            (stream.to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_rustfmt_fallback() {
+        let stream = compile_str("node E -> O {}\nnode O {}\ngraph G(E)").unwrap();
+        let (pretty, _) = format(
+            Formatter::Prettyplease,
+            Path::new("rustfmt"),
+            stream.clone(),
+        )
+        .unwrap();
+
+        // Only a missing rustfmt falls back to prettyplease.
+        let missing = Path::new("/nonexistent/rustfmt");
+        let (code, warning) = format(Formatter::Rustfmt, missing, stream.clone()).unwrap();
+        assert_eq!(code, pretty);
+        assert!(warning.unwrap().starts_with("cannot run rustfmt"));
+
+        let result = format(Formatter::Rustfmt, &std::env::temp_dir(), stream);
+        assert!(matches!(result, Err(Error::Format(_))), "{result:?}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rustfmt_error() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // A rustfmt exiting without reading its input, which is large enough to fill the pipe.
+        let bin = std::env::temp_dir().join(format!("static-graph-rustfmt-{}", std::process::id()));
+        std::fs::write(&bin, "#!/bin/sh\necho 'rustfmt failed' >&2\nexit 1\n").unwrap();
+        std::fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let graphs: String = (0..500)
+            .map(|i| format!("node N{i} {{}}\ngraph G{i}(N{i})\n"))
+            .collect();
+        let stream = compile_str(&graphs).unwrap();
+        match format(Formatter::Rustfmt, &bin, stream) {
+            Err(Error::Format(message)) => assert_eq!(message, "rustfmt failed\n"),
+            other => panic!("{other:?}"),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::ops::Deref;
+
+use nom::{
+    bytes::complete::take_while,
+    character::complete::satisfy,
+    combinator::{cut, map, opt, recognize},
+    multi::many1,
+    sequence::tuple,
+};
+
+use super::{
+    blank, expect, list_separator, literal::Literal, spanned, token, Expected, IResult, Input,
+    Parser, Rebase, Span,
+};
+
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    pub key: String,
+    pub value: Literal,
+    pub span: Span,
+}
+
+impl Rebase for Annotation {
+    fn rebase(&mut self, base: usize) {
+        self.value.rebase(base);
+        self.span.rebase(base);
+    }
+}
+
+impl Rebase for Annotations {
+    fn rebase(&mut self, base: usize) {
+        self.0.rebase(base);
+    }
+}
+
+impl Deref for Annotations {
+    type Target = Vec<Annotation>;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Annotations(pub Vec<Annotation>);
+
+impl<'a> Parser<'a> for Annotations {
+    fn parse(input: Input<'a>) -> IResult<'a, Annotations> {
+        map(
+            tuple((
+                token("#["),
+                cut(many1(map(
+                    tuple((
+                        opt(blank),
+                        spanned(tuple((
+                            expect(
+                                Expected::Label("annotation key"),
+                                recognize(tuple((
+                                    satisfy(|c| c.is_ascii_alphabetic() || c == '_'),
+                                    take_while(|c: char| {
+                                        c.is_ascii_alphanumeric() || c == '_' || c == '.'
+                                    }),
+                                ))),
+                            ),
+                            opt(blank),
+                            token("="),
+                            opt(blank),
+                            Literal::parse,
+                        ))),
+                        opt(blank),
+                        opt(list_separator),
+                    )),
+                    |(_, ((p, _, _, _, lit), span), _, _)| Annotation {
+                        key: p.fragment().to_string(),
+                        value: lit,
+                        span,
+                    },
+                ))),
+                opt(blank),
+                cut(token("]")),
+            )),
+            |(_, annotations, _, _)| Annotations(annotations),
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotations() {
+        match Annotations::parse(Input::new(r#"#[foo = "bar"]"#)) {
+            Ok((remain, annotations)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(annotations.len(), 1);
+                assert_eq!(annotations[0].key, "foo");
+                assert_eq!(annotations[0].value.0, "bar");
+                assert_eq!(annotations[0].span, Span::new(2, 13));
+            }
+            Err(e) => panic!("{e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_annotations_missing_eq() {
+        match Annotations::parse(Input::new(r#"#[foo "bar"]"#)) {
+            Err(nom::Err::Failure(e)) => {
+                assert_eq!(e.offset, 6);
+                assert_eq!(e.message(), "expected `=`");
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use nom::{
+    branch::alt,
+    combinator::{map, opt, peek},
+};
+
+use super::{
+    blank, graph::Graph, import::Import, module::Module, node::Node, subgraph::Use, token, IResult,
+    Input, Parser, Rebase,
+};
+use crate::diagnostic::Diagnostic;
+
+#[derive(Debug, Clone, Default)]
+pub struct Document {
+    pub graphs: Vec<Graph>,
+    pub nodes: Vec<Node>,
+    pub uses: Vec<Use>,
+    pub mods: Vec<Module>,
+    /// Only written at the top level of a file.
+    pub imports: Vec<Import>,
+}
+
+impl Rebase for Document {
+    fn rebase(&mut self, base: usize) {
+        self.graphs.rebase(base);
+        self.nodes.rebase(base);
+        self.uses.rebase(base);
+        self.mods.rebase(base);
+        self.imports.rebase(base);
+    }
+}
+
+impl<'a> Parser<'a> for Document {
+    /// Parse a whole document, failing on the first item that is not a node, a graph, a `use`
+    /// of a graph, a `mod` block or an import.
+    fn parse(input: Input<'a>) -> IResult<'a, Self> {
+        Self::items(input, false)
+    }
+}
+
+impl Document {
+    /// Move the items of `other` after the items of this document.
+    pub fn append(&mut self, other: Document) {
+        let Document {
+            graphs,
+            nodes,
+            uses,
+            mods,
+            imports,
+        } = other;
+        self.graphs.extend(graphs);
+        self.nodes.extend(nodes);
+        self.uses.extend(uses);
+        self.mods.extend(mods);
+        self.imports.extend(imports);
+    }
+
+    /// The items of a `mod` block, up to its closing `}`.
+    pub(super) fn items_in_mod(input: Input) -> IResult<Self> {
+        Self::items(input, true)
+    }
+
+    fn items(input: Input, in_mod: bool) -> IResult<Self> {
+        enum Item {
+            Node(Node),
+            Graph(Graph),
+            Use(Use),
+            Mod(Module),
+            Import(Import),
+            End,
+        }
+
+        let mut document = Document::default();
+        let (mut input, _) = opt(blank)(input)?;
+        while in_mod || !input.fragment().is_empty() {
+            let (rest, item) = if in_mod {
+                alt((
+                    map(Node::parse, Item::Node),
+                    map(Graph::parse, Item::Graph),
+                    map(Use::parse, Item::Use),
+                    map(Module::parse, Item::Mod),
+                    map(peek(token("}")), |_| Item::End),
+                ))(input)?
+            } else {
+                alt((
+                    map(Node::parse, Item::Node),
+                    map(Graph::parse, Item::Graph),
+                    map(Use::parse, Item::Use),
+                    map(Module::parse, Item::Mod),
+                    map(Import::parse, Item::Import),
+                ))(input)?
+            };
+            match item {
+                Item::Node(node) => document.nodes.push(node),
+                Item::Graph(graph) => document.graphs.push(graph),
+                Item::Use(u) => document.uses.push(u),
+                Item::Mod(module) => document.mods.push(module),
+                Item::Import(import) => document.imports.push(import),
+                Item::End => break,
+            }
+            (input, _) = opt(blank)(rest)?;
+        }
+        Ok((input, document))
+    }
+}
+
+/// Parse `src` as a complete document.
+pub fn parse_document(src: &str) -> Result<Document, Diagnostic> {
+    match Document::parse(Input::new(src)) {
+        Ok((_, document)) => Ok(document),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(e.to_diagnostic()),
+        Err(nom::Err::Incomplete(_)) => Err(Diagnostic::error(
+            "unexpected end of input",
+            crate::parser::Span::new(src.len(), src.len()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document() {
+        let input = r#"
+        graph Foo(Bar)
+
+        node Bar -> (Baz, Qux) {
+            #[default = "B::new"]
+            b: B,
+        }
+
+        node Baz -> Out {
+            #[default = "C::new"]
+            c: C,
+        }
+
+        node Qux -> Out {
+            #[default = "D::new"]
+            d: D,
+        }
+
+        node Out {
+
+        }
+        "#;
+
+        match super::Document::parse(Input::new(input)) {
+            Ok((remain, doc)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(doc.graphs.len(), 1);
+                assert_eq!(doc.nodes.len(), 4);
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_document_trailing_garbage() {
+        let input = "node A {}\nnod B {}";
+        match super::Document::parse(Input::new(input)) {
+            Err(nom::Err::Error(e)) => {
+                assert_eq!(e.offset, 10);
+                assert_eq!(
+                    e.message(),
+                    "expected `node`, `switch`, `graph`, `use`, `mod` or `import`"
+                );
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+}
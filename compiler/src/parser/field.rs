@@ -1,36 +1,51 @@
 use nom::{
-    bytes::complete::tag,
     combinator::{map, opt},
     sequence::tuple,
-    IResult,
 };
 
-use super::{annotations::Annotations, blank, ident::Ident, list_separator, ty::Type, Parser};
+use super::{
+    annotations::Annotations, blank, ident::Ident, list_separator, spanned, token, ty::Type,
+    IResult, Input, Parser, Rebase, Span,
+};
 
 #[derive(Debug, Clone)]
 pub struct Field {
     pub name: Ident,
     pub ty: Type,
     pub annotations: Annotations,
+    pub span: Span,
+}
+
+impl Rebase for Field {
+    fn rebase(&mut self, base: usize) {
+        self.name.rebase(base);
+        self.ty.rebase(base);
+        self.annotations.rebase(base);
+        self.span.rebase(base);
+    }
 }
 
 impl<'a> Parser<'a> for Field {
-    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+    fn parse(input: Input<'a>) -> IResult<'a, Self> {
         map(
             tuple((
-                opt(Annotations::parse),
-                opt(blank),
-                Ident::parse,
-                tag(":"),
-                opt(blank),
-                Type::parse,
+                spanned(tuple((
+                    opt(Annotations::parse),
+                    opt(blank),
+                    Ident::parse,
+                    opt(blank),
+                    token(":"),
+                    opt(blank),
+                    Type::parse,
+                ))),
                 opt(blank),
                 opt(list_separator),
             )),
-            |(annotations, _, name, _, _, ty, _, _)| Field {
+            |(((annotations, _, name, _, _, _, ty), span), _, _)| Field {
                 name,
                 ty,
                 annotations: annotations.unwrap_or_default(),
+                span,
             },
         )(input)
     }
@@ -43,11 +58,13 @@ mod tests {
 
     #[test]
     fn test_field() {
-        let input = r#"#[default = "Bar::new"]
-        foo: Bar"#;
+        let input = Input::new(
+            r#"#[default = "Bar::new"]
+        foo: Bar"#,
+        );
         match super::Field::parse(input) {
             Ok((remain, field)) => {
-                assert_eq!(remain, "");
+                assert_eq!(*remain.fragment(), "");
                 assert_eq!(field.name.0, "foo");
                 match field.ty {
                     Type::Path(path) => {
@@ -59,6 +76,7 @@ mod tests {
                 assert_eq!(field.annotations.len(), 1);
                 assert_eq!(field.annotations[0].key, "default");
                 assert_eq!(field.annotations[0].value.0, "Bar::new");
+                assert_eq!(field.span, Span::new(0, 40));
             }
             Err(e) => panic!("{e:?}"),
         }
@@ -0,0 +1,143 @@
+use nom::{
+    combinator::{cut, map, opt},
+    sequence::{preceded, tuple},
+};
+
+use super::{
+    annotations::Annotations, blank, ident::Ident, path::Path, spanned, token, ty::Type, IResult,
+    Input, Parser, Rebase, Span,
+};
+
+#[derive(Debug, Clone)]
+pub struct Graph {
+    pub name: Ident,
+    pub entry_node: Path,
+    /// Declared with `graph G(E): Req -> Resp, error Error`; every part is optional.
+    pub req_ty: Option<Type>,
+    pub resp_ty: Option<Type>,
+    pub error_ty: Option<Type>,
+    /// Written before `graph`.
+    pub annotations: Annotations,
+    pub span: Span,
+}
+
+impl Rebase for Graph {
+    fn rebase(&mut self, base: usize) {
+        self.name.rebase(base);
+        self.entry_node.rebase(base);
+        self.req_ty.rebase(base);
+        self.resp_ty.rebase(base);
+        self.error_ty.rebase(base);
+        self.annotations.rebase(base);
+        self.span.rebase(base);
+    }
+}
+
+impl<'a> Parser<'a> for Graph {
+    fn parse(input: Input<'a>) -> IResult<'a, Self> {
+        map(
+            tuple((
+                opt(tuple((Annotations::parse, opt(blank)))),
+                spanned(preceded(
+                    tuple((token("graph"), blank)),
+                    cut(tuple((
+                        Ident::parse,
+                        opt(blank),
+                        token("("),
+                        opt(blank),
+                        Path::parse,
+                        opt(blank),
+                        token(")"),
+                        opt(preceded(tuple((opt(blank), token(":"))), cut(signature))),
+                    ))),
+                )),
+            )),
+            |(annotations, ((name, _, _, _, entry, _, _, signature), span))| {
+                let (req_ty, resp_ty, error_ty) = signature.unwrap_or_default();
+                Graph {
+                    name,
+                    entry_node: entry,
+                    req_ty,
+                    resp_ty,
+                    error_ty,
+                    annotations: annotations.map(|(a, _)| a).unwrap_or_default(),
+                    span,
+                }
+            },
+        )(input)
+    }
+}
+
+type Signature = (Option<Type>, Option<Type>, Option<Type>);
+
+/// `Req -> Resp, error Error`, the part of a graph declaration after `:`.
+fn signature(input: Input) -> IResult<Signature> {
+    map(
+        tuple((
+            opt(blank),
+            Type::parse,
+            opt(preceded(
+                tuple((opt(blank), token("->"), opt(blank))),
+                Type::parse,
+            )),
+            opt(preceded(
+                tuple((opt(blank), token(","), opt(blank), token("error"), blank)),
+                Type::parse,
+            )),
+        )),
+        |(_, req, resp, error)| (Some(req), resp, error),
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph() {
+        let input = Input::new("graph Foo(Bar)");
+        match super::Graph::parse(input) {
+            Ok((remain, graph)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(graph.name.0, "Foo");
+                assert_eq!(graph.entry_node.to_string(), "Bar");
+                assert_eq!(graph.span, Span::new(0, 14));
+                assert!(graph.req_ty.is_none());
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_graph_signature() {
+        let input = Input::new("graph Foo(Bar): crate::Req -> crate::Out, error crate::Err");
+        match super::Graph::parse(input) {
+            Ok((remain, graph)) => {
+                assert_eq!(*remain.fragment(), "");
+                match (graph.req_ty, graph.resp_ty, graph.error_ty) {
+                    (Some(Type::Path(req)), Some(Type::Path(resp)), Some(Type::Path(error))) => {
+                        assert_eq!(req.segments[1].0, "Req");
+                        assert_eq!(resp.segments[1].0, "Out");
+                        assert_eq!(error.segments[1].0, "Err");
+                    }
+                    other => panic!("{other:?}"),
+                }
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_graph_annotations() {
+        let input = Input::new("#[owner = \"search\"] graph Foo(Bar)");
+        match super::Graph::parse(input) {
+            Ok((remain, graph)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(graph.annotations.len(), 1);
+                assert_eq!(graph.annotations[0].key, "owner");
+                assert_eq!(graph.span, Span::new(20, 34));
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use faststr::FastStr;
+use nom::{
+    bytes::complete::take_while,
+    character::complete::{char, satisfy},
+    combinator::{map, recognize},
+    multi::many0,
+    sequence::tuple,
+};
+
+use std::ops::Deref;
+
+use super::{expect, spanned, Expected, IResult, Input, Parser, Rebase, Span};
+
+#[derive(Debug, Clone)]
+pub struct Ident(pub FastStr, pub Span);
+
+impl Ident {
+    pub fn span(&self) -> Span {
+        self.1
+    }
+}
+
+impl Rebase for Ident {
+    fn rebase(&mut self, base: usize) {
+        self.1.rebase(base);
+    }
+}
+
+impl Deref for Ident {
+    type Target = FastStr;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a> Parser<'a> for Ident {
+    fn parse(input: Input<'a>) -> IResult<'a, Ident> {
+        map(
+            spanned(expect(
+                Expected::Label("identifier"),
+                recognize(tuple((
+                    many0(char('_')),
+                    satisfy(|c| c.is_ascii_alphabetic()),
+                    take_while(|c: char| c.is_ascii_alphanumeric() || c == '_'),
+                ))),
+            )),
+            |(ident, span)| -> Ident { Ident(FastStr::new(*ident.fragment()), span) },
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ident() {
+        let input = Input::new("_Foo");
+        match super::Ident::parse(input) {
+            Ok((remain, ident)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(ident.0, "_Foo");
+                assert_eq!(ident.span(), Span::new(0, 4));
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+}
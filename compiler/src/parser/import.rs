@@ -0,0 +1,49 @@
+use nom::{
+    combinator::{cut, map, opt},
+    sequence::{preceded, tuple},
+};
+
+use super::{blank, literal::Literal, spanned, token, IResult, Input, Parser, Rebase, Span};
+
+/// `import "common.graph";`, with the path relative to the importing file.
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub path: Literal,
+    pub span: Span,
+}
+
+impl Rebase for Import {
+    fn rebase(&mut self, base: usize) {
+        self.path.rebase(base);
+        self.span.rebase(base);
+    }
+}
+
+impl<'a> Parser<'a> for Import {
+    fn parse(input: Input<'a>) -> IResult<'a, Self> {
+        map(
+            spanned(preceded(
+                tuple((token("import"), blank)),
+                cut(tuple((Literal::parse, opt(blank), token(";")))),
+            )),
+            |((path, _, _), span)| Import { path, span },
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import() {
+        match super::Import::parse(Input::new(r#"import "common.graph";"#)) {
+            Ok((remain, import)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(&*import.path, "common.graph");
+                assert_eq!(import.span, Span::new(0, 22));
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+}
@@ -6,13 +6,18 @@ use nom::{
     character::complete::{none_of, one_of},
     combinator::map,
     sequence::delimited,
-    IResult,
 };
 
-use super::Parser;
+use super::{expect, spanned, Expected, IResult, Input, Parser, Rebase, Span};
 
 #[derive(Debug, Clone)]
-pub struct Literal(pub String);
+pub struct Literal(pub String, pub Span);
+
+impl Rebase for Literal {
+    fn rebase(&mut self, base: usize) {
+        self.1.rebase(base);
+    }
+}
 
 impl Deref for Literal {
     type Target = str;
@@ -22,15 +27,18 @@ impl Deref for Literal {
 }
 
 impl<'a> Parser<'a> for Literal {
-    fn parse(input: &'a str) -> IResult<&'a str, Literal> {
-        alt((
-            map(single_quote, |x| Literal(x.into())),
-            map(double_quote, |x| Literal(x.into())),
-        ))(input)
+    fn parse(input: Input<'a>) -> IResult<'a, Literal> {
+        map(
+            spanned(expect(
+                Expected::Label("string literal"),
+                alt((single_quote, double_quote)),
+            )),
+            |(x, span)| Literal(x.fragment().to_string(), span),
+        )(input)
     }
 }
 
-fn single_quote(input: &str) -> IResult<&str, &str> {
+fn single_quote(input: Input) -> IResult<Input> {
     let esc = escaped(none_of(r#"\'"#), '\\', one_of(r#"'"n\"#));
     let esc_or_empty = alt((esc, tag("")));
     let res = delimited(tag("\'"), esc_or_empty, tag("\'"))(input)?;
@@ -38,7 +46,7 @@ fn single_quote(input: &str) -> IResult<&str, &str> {
     Ok(res)
 }
 
-fn double_quote(input: &str) -> IResult<&str, &str> {
+fn double_quote(input: Input) -> IResult<Input> {
     let esc = escaped(none_of(r#"\""#), '\\', one_of(r#"'"n\"#));
     let esc_or_empty = alt((esc, tag("")));
     let res = delimited(tag("\""), esc_or_empty, tag("\""))(input)?;
@@ -52,11 +60,12 @@ mod tests {
 
     #[test]
     fn test_literal() {
-        let input = r#""foo""#;
+        let input = Input::new(r#""foo""#);
         match super::Literal::parse(input) {
             Ok((remain, lit)) => {
-                assert_eq!(remain, "");
+                assert_eq!(*remain.fragment(), "");
                 assert_eq!(lit.0, "foo");
+                assert_eq!(lit.1, Span::new(0, 5));
             }
             Err(e) => panic!("Error: {e:?}"),
         }
@@ -0,0 +1,201 @@
+pub mod annotations;
+pub mod document;
+pub mod field;
+pub mod graph;
+pub mod ident;
+pub mod import;
+pub mod literal;
+pub mod module;
+pub mod node;
+pub mod path;
+pub mod subgraph;
+pub mod ty;
+
+use std::fmt;
+
+use nom::character::complete::{multispace1, one_of};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till, take_until},
+    combinator::map,
+    error::ErrorKind,
+    multi::many1,
+    sequence::{preceded, terminated},
+};
+use nom_locate::LocatedSpan;
+
+use crate::diagnostic::Diagnostic;
+
+pub type Input<'a> = LocatedSpan<&'a str>;
+
+pub type IResult<'a, O> = nom::IResult<Input<'a>, O, ParseError>;
+
+pub trait Parser<'a>: Sized {
+    fn parse(input: Input<'a>) -> IResult<'a, Self>;
+}
+
+/// A byte range in the source a node of the AST was parsed from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    fn between(start: &Input, end: &Input) -> Self {
+        Self::new(start.location_offset(), end.location_offset())
+    }
+}
+
+/// Shift every span by `base`, placing the AST of one file among the other files of a
+/// document.
+pub(crate) trait Rebase {
+    fn rebase(&mut self, base: usize);
+}
+
+impl Rebase for Span {
+    fn rebase(&mut self, base: usize) {
+        self.start += base;
+        self.end += base;
+    }
+}
+
+impl<T: Rebase> Rebase for Option<T> {
+    fn rebase(&mut self, base: usize) {
+        if let Some(inner) = self {
+            inner.rebase(base);
+        }
+    }
+}
+
+impl<T: Rebase> Rebase for Vec<T> {
+    fn rebase(&mut self, base: usize) {
+        for inner in self {
+            inner.rebase(base);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    Token(&'static str),
+    Label(&'static str),
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Token(token) => write!(f, "`{token}`"),
+            Expected::Label(label) => f.write_str(label),
+        }
+    }
+}
+
+/// The furthest point the parser reached and what it would have accepted there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub offset: usize,
+    pub expected: Vec<Expected>,
+}
+
+impl ParseError {
+    pub fn message(&self) -> String {
+        match self.expected.as_slice() {
+            [] => "unexpected input".to_string(),
+            [one] => format!("expected {one}"),
+            [init @ .., last] => {
+                let init = init
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("expected {init} or {last}")
+            }
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::error(self.message(), Span::new(self.offset, self.offset))
+    }
+}
+
+impl<'a> nom::error::ParseError<Input<'a>> for ParseError {
+    fn from_error_kind(input: Input<'a>, _kind: ErrorKind) -> Self {
+        Self {
+            offset: input.location_offset(),
+            expected: Vec::new(),
+        }
+    }
+
+    fn append(_input: Input<'a>, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        match self.offset.cmp(&other.offset) {
+            std::cmp::Ordering::Less => other,
+            std::cmp::Ordering::Greater => self,
+            std::cmp::Ordering::Equal => {
+                for expected in other.expected {
+                    if !self.expected.contains(&expected) {
+                        self.expected.push(expected);
+                    }
+                }
+                self
+            }
+        }
+    }
+}
+
+/// Replace whatever `parser` expected with `expected` when it fails without consuming input.
+pub(crate) fn expect<'a, O>(
+    expected: Expected,
+    mut parser: impl FnMut(Input<'a>) -> IResult<'a, O>,
+) -> impl FnMut(Input<'a>) -> IResult<'a, O> {
+    move |input: Input<'a>| {
+        let offset = input.location_offset();
+        parser(input).map_err(|e| {
+            e.map(|mut e| {
+                if e.offset == offset {
+                    e.expected = vec![expected];
+                }
+                e
+            })
+        })
+    }
+}
+
+pub(crate) fn token<'a>(token: &'static str) -> impl FnMut(Input<'a>) -> IResult<'a, Input<'a>> {
+    expect(Expected::Token(token), tag(token))
+}
+
+/// Run `parser` and record the span of the input it consumed.
+pub(crate) fn spanned<'a, O>(
+    mut parser: impl FnMut(Input<'a>) -> IResult<'a, O>,
+) -> impl FnMut(Input<'a>) -> IResult<'a, (O, Span)> {
+    move |input: Input<'a>| {
+        let start = input;
+        let (rest, output) = parser(input)?;
+        Ok((rest, (output, Span::between(&start, &rest))))
+    }
+}
+
+fn comment(input: Input) -> IResult<Input> {
+    alt((
+        preceded(tag("//"), take_till(|c| c == '\n')),
+        preceded(tag("/*"), terminated(take_until("*/"), tag("*/"))),
+    ))(input)
+}
+
+pub(crate) fn blank(input: Input) -> IResult<()> {
+    map(many1(alt((comment, multispace1))), |_| ())(input)
+}
+
+pub(crate) fn list_separator(input: Input) -> IResult<char> {
+    expect(Expected::Token(","), one_of(",;"))(input)
+}
@@ -0,0 +1,76 @@
+use nom::{
+    combinator::{cut, map, opt},
+    sequence::{preceded, tuple},
+};
+
+use super::{
+    blank, document::Document, ident::Ident, spanned, token, IResult, Input, Parser, Rebase, Span,
+};
+
+/// `mod recall { .. }`, a namespace of its own for the items inside.
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub name: Ident,
+    pub items: Document,
+    pub span: Span,
+}
+
+impl Rebase for Module {
+    fn rebase(&mut self, base: usize) {
+        self.name.rebase(base);
+        self.items.rebase(base);
+        self.span.rebase(base);
+    }
+}
+
+impl<'a> Parser<'a> for Module {
+    fn parse(input: Input<'a>) -> IResult<'a, Self> {
+        map(
+            spanned(preceded(
+                tuple((token("mod"), blank)),
+                cut(tuple((
+                    Ident::parse,
+                    opt(blank),
+                    token("{"),
+                    Document::items_in_mod,
+                    token("}"),
+                ))),
+            )),
+            |((name, _, _, items, _), span)| Module { name, items, span },
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module() {
+        let input = Input::new("mod recall {\n    node A {}\n    mod inner { node B {} }\n}");
+        match super::Module::parse(input) {
+            Ok((remain, module)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(module.name.0, "recall");
+                assert_eq!(module.items.nodes.len(), 1);
+                assert_eq!(module.items.mods[0].items.nodes[0].name.0, "B");
+                assert_eq!(module.span, Span::new(0, input.len()));
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_module_unclosed() {
+        match super::Module::parse(Input::new("mod recall { node A {}")) {
+            Err(nom::Err::Failure(e)) => {
+                assert_eq!(e.offset, 22);
+                assert_eq!(
+                    e.message(),
+                    "expected `node`, `switch`, `graph`, `use`, `mod` or `}`"
+                );
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use super::{
+    annotations::Annotations, blank, field::Field, ident::Ident, list_separator, path::Path,
+    spanned, subgraph::edges, token, ty::Type, IResult, Input, Parser, Rebase, Span,
+};
+
+use nom::{
+    branch::alt,
+    combinator::opt,
+    combinator::{cut, map},
+    multi::many0,
+    sequence::{delimited, preceded, tuple},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Node,
+    /// Runs exactly one of its successors, chosen by its response.
+    Switch,
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub name: Ident,
+    pub to_nodes: Vec<Edge>,
+    /// Declared with `node X -> O: Resp`.
+    pub resp_ty: Option<Type>,
+    pub fields: Vec<Field>,
+    /// Written before `node`, e.g. `#[timeout_ms = "50"]`.
+    pub annotations: Annotations,
+    pub span: Span,
+}
+
+/// An edge to a successor, e.g. `X`, `?X`, `recall::X`, `X if crate::is_new_user` or
+/// `use Profile -> Rank`.
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub to: Path,
+    /// Written `?X`: the successor runs even when the node the edge leaves fails.
+    pub soft: bool,
+    /// Written `each X`: the successor runs once per item of the response.
+    pub each: bool,
+    /// Written `use G`: the successor is graph `G`, run in place as a single node.
+    pub subgraph: bool,
+    /// Written `use G -> X`: the successors of this run of `G`, which is then a node of its
+    /// own rather than the one declared by the `use` item of `G`.
+    pub successors: Option<Vec<Edge>>,
+    /// A predicate over the request and the response of the node the edge leaves; the
+    /// successor is skipped when it returns `false`.
+    pub condition: Option<Path>,
+}
+
+impl Rebase for Edge {
+    fn rebase(&mut self, base: usize) {
+        self.to.rebase(base);
+        self.successors.rebase(base);
+        self.condition.rebase(base);
+    }
+}
+
+impl<'a> Parser<'a> for Edge {
+    fn parse(input: Input<'a>) -> IResult<'a, Self> {
+        map(
+            tuple((
+                opt(tuple((token("?"), opt(blank)))),
+                alt((
+                    map(preceded(tuple((token("each"), blank)), Path::parse), |to| {
+                        (true, false, to, None)
+                    }),
+                    map(
+                        preceded(
+                            tuple((token("use"), blank)),
+                            tuple((
+                                Path::parse,
+                                opt(preceded(
+                                    tuple((opt(blank), token("->"), opt(blank))),
+                                    cut(edges),
+                                )),
+                            )),
+                        ),
+                        |(to, successors)| (false, true, to, successors),
+                    ),
+                    map(Path::parse, |to| (false, false, to, None)),
+                )),
+                opt(preceded(
+                    tuple((blank, token("if"), blank)),
+                    cut(Path::parse),
+                )),
+            )),
+            |(soft, (each, subgraph, to, successors), condition)| Edge {
+                to,
+                soft: soft.is_some(),
+                each,
+                subgraph,
+                successors,
+                condition,
+            },
+        )(input)
+    }
+}
+
+impl Rebase for Node {
+    fn rebase(&mut self, base: usize) {
+        self.name.rebase(base);
+        self.to_nodes.rebase(base);
+        self.resp_ty.rebase(base);
+        self.fields.rebase(base);
+        self.annotations.rebase(base);
+        self.span.rebase(base);
+    }
+}
+
+impl<'a> Parser<'a> for Node {
+    fn parse(input: Input<'a>) -> IResult<'a, Self> {
+        map(
+            tuple((
+                opt(tuple((Annotations::parse, opt(blank)))),
+                spanned(tuple((
+                    alt((
+                        map(token("node"), |_| NodeKind::Node),
+                        map(token("switch"), |_| NodeKind::Switch),
+                    )),
+                    blank,
+                    cut(tuple((
+                        Ident::parse,
+                        opt(blank),
+                        alt((
+                            map(
+                                tuple((
+                                    token("->"),
+                                    opt(blank),
+                                    delimited(
+                                        opt(token("(")),
+                                        many0(map(
+                                            tuple((opt(blank), Edge::parse, opt(list_separator))),
+                                            |(_, edge, _)| edge,
+                                        )),
+                                        opt(token(")")),
+                                    ),
+                                    opt(blank),
+                                    resp_ty,
+                                )),
+                                |(_, _, edges, _, resp_ty)| (edges, resp_ty),
+                            ),
+                            map(resp_ty, |resp_ty| (Vec::new(), resp_ty)),
+                        )),
+                        fields,
+                    ))),
+                ))),
+            )),
+            |(annotations, ((kind, _, (name, _, (to_nodes, resp_ty), fields)), span))| Node {
+                kind,
+                name,
+                to_nodes,
+                resp_ty,
+                fields,
+                annotations: annotations.map(|(a, _)| a).unwrap_or_default(),
+                span,
+            },
+        )(input)
+    }
+}
+
+/// An optional `: Resp` followed by the `{` opening the node body.
+fn resp_ty(input: Input) -> IResult<Option<Type>> {
+    alt((
+        map(
+            tuple((token(":"), opt(blank), Type::parse, opt(blank), token("{"))),
+            |(_, _, ty, _, _)| Some(ty),
+        ),
+        map(token("{"), |_| None),
+    ))(input)
+}
+
+/// Fields up to and including the closing `}` of a node body.
+fn fields(mut input: Input) -> IResult<Vec<Field>> {
+    let mut fields = Vec::new();
+    loop {
+        let (rest, _) = opt(blank)(input)?;
+        let (rest, field) = alt((map(token("}"), |_| None), map(Field::parse, Some)))(rest)?;
+        input = rest;
+        match field {
+            Some(field) => fields.push(field),
+            None => return Ok((input, fields)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node() {
+        let input = Input::new(
+            r#"node Foo {
+            #[default = "Bar::new"]
+            foo: Bar,
+        }"#,
+        );
+        match super::Node::parse(input) {
+            Ok((remain, node)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(node.name.0, "Foo");
+                assert_eq!(node.to_nodes.len(), 0);
+                assert_eq!(node.fields.len(), 1);
+                assert_eq!(node.fields[0].name.0, "foo");
+                match &node.fields[0].ty {
+                    Type::Path(path) => {
+                        assert_eq!(path.segments.len(), 1);
+                        assert_eq!(path.segments[0].0, "Bar");
+                    }
+                    _ => panic!("Expected Path"),
+                }
+                assert_eq!(node.fields[0].annotations.len(), 1);
+                assert_eq!(node.fields[0].annotations[0].key, "default");
+                assert_eq!(node.span, Span::new(0, input.len()));
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_node_missing_arrow() {
+        match super::Node::parse(Input::new("node X - O {}")) {
+            Err(nom::Err::Failure(e)) => {
+                assert_eq!(e.offset, 7);
+                assert_eq!(e.message(), "expected `->`, `:` or `{`");
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_node_annotations() {
+        let input = Input::new(
+            r#"#[timeout_ms = "50"]
+        node X -> Y {}"#,
+        );
+        match super::Node::parse(input) {
+            Ok((remain, node)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(node.annotations.len(), 1);
+                assert_eq!(node.annotations[0].key, "timeout_ms");
+                assert_eq!(node.annotations[0].value.0, "50");
+                assert_eq!(node.span.start, input.find("node").unwrap());
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_node_conditions() {
+        match super::Node::parse(Input::new("node E -> (X if crate::is_new_user, Y) {}")) {
+            Ok((remain, node)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(node.to_nodes.len(), 2);
+                assert_eq!(node.to_nodes[0].to.to_string(), "X");
+                let condition = node.to_nodes[0].condition.as_ref().unwrap();
+                assert_eq!(condition.segments[1].0, "is_new_user");
+                assert!(node.to_nodes[1].condition.is_none());
+                assert!(!node.to_nodes[1].soft);
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+        match super::Node::parse(Input::new("node E -> X if p: crate::EResp {}")) {
+            Ok((_, node)) => {
+                assert_eq!(
+                    node.to_nodes[0].condition.as_ref().unwrap().segments[0].0,
+                    "p"
+                );
+                assert!(node.resp_ty.is_some());
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_node_soft_edges() {
+        match super::Node::parse(Input::new("node P -> (?render::O, X) {}")) {
+            Ok((remain, node)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(node.to_nodes[0].to.to_string(), "render::O");
+                assert!(node.to_nodes[0].soft);
+                assert!(!node.to_nodes[1].soft);
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_node_each_edges() {
+        match super::Node::parse(Input::new("node Recall -> (each Score, each) {}")) {
+            Ok((remain, node)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(node.to_nodes[0].to.to_string(), "Score");
+                assert!(node.to_nodes[0].each);
+                assert_eq!(node.to_nodes[1].to.to_string(), "each");
+                assert!(!node.to_nodes[1].each);
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_node_use_edges() {
+        match super::Node::parse(Input::new("node Main -> (use common::Profile, user) {}")) {
+            Ok((remain, node)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(node.to_nodes[0].to.to_string(), "common::Profile");
+                assert!(node.to_nodes[0].subgraph);
+                assert_eq!(node.to_nodes[1].to.to_string(), "user");
+                assert!(!node.to_nodes[1].subgraph);
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_node_use_successors() {
+        match super::Node::parse(Input::new(
+            "node Main -> (use Profile -> (Rank, ?Log), use Profile -> Rank, X) {}",
+        )) {
+            Ok((remain, node)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(node.to_nodes.len(), 3);
+                let successors = node.to_nodes[0].successors.as_ref().unwrap();
+                assert_eq!(successors.len(), 2);
+                assert!(successors[1].soft);
+                assert_eq!(node.to_nodes[1].successors.as_ref().unwrap().len(), 1);
+                assert!(node.to_nodes[2].successors.is_none());
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+        match super::Node::parse(Input::new("node Main -> use Profile -> (Rank) if p {}")) {
+            Ok((_, node)) => {
+                assert_eq!(node.to_nodes.len(), 1);
+                assert_eq!(
+                    node.to_nodes[0].condition.as_ref().unwrap().to_string(),
+                    "p"
+                );
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_switch() {
+        match super::Node::parse(Input::new("switch S -> (Hit, Miss): crate::Key {}")) {
+            Ok((remain, node)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(node.kind, NodeKind::Switch);
+                assert_eq!(node.to_nodes.len(), 2);
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_node_resp_ty() {
+        match super::Node::parse(Input::new("node X -> (Y, Z): crate::XResp {}")) {
+            Ok((remain, node)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(node.to_nodes.len(), 2);
+                match node.resp_ty {
+                    Some(Type::Path(path)) => assert_eq!(path.segments[1].0, "XResp"),
+                    other => panic!("{other:?}"),
+                }
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use nom::{
+    bytes::complete::tag,
+    combinator::{map, opt},
+    multi::separated_list1,
+    sequence::tuple,
+};
+
+use super::{blank, ident::Ident, spanned, IResult, Input, Parser, Rebase, Span};
+
+#[derive(Debug, Clone)]
+pub struct Path {
+    pub segments: Arc<[Ident]>,
+    pub span: Span,
+}
+
+impl Rebase for Path {
+    fn rebase(&mut self, base: usize) {
+        self.segments = self
+            .segments
+            .iter()
+            .map(|segment| {
+                let mut segment = segment.clone();
+                segment.rebase(base);
+                segment
+            })
+            .collect();
+        self.span.rebase(base);
+    }
+}
+
+impl std::fmt::Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                f.write_str("::")?;
+            }
+            f.write_str(segment)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parser<'a> for Path {
+    fn parse(input: Input<'a>) -> IResult<'a, Self> {
+        map(
+            spanned(separated_list1(
+                tuple((opt(blank), tag("::"), opt(blank))),
+                Ident::parse,
+            )),
+            |(idents, span)| Path {
+                segments: Arc::from(idents),
+                span,
+            },
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        let input = Input::new("Foo::Bar::Baz");
+        match super::Path::parse(input) {
+            Ok((remain, path)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(path.segments.len(), 3);
+                assert_eq!(path.segments[0].0, "Foo");
+                assert_eq!(path.segments[1].0, "Bar");
+                assert_eq!(path.segments[2].0, "Baz");
+                assert_eq!(path.span, Span::new(0, 13));
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+}
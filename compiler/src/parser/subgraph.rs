@@ -0,0 +1,115 @@
+use nom::{
+    branch::alt,
+    combinator::{cut, map, opt},
+    multi::many0,
+    sequence::{delimited, preceded, tuple},
+};
+
+use super::{
+    annotations::Annotations, blank, list_separator, node::Edge, path::Path, spanned, token,
+    IResult, Input, Parser, Rebase, Span,
+};
+
+/// The successors of a graph used as a node, e.g. `use Profile -> (Rank, Log);`.
+#[derive(Debug, Clone)]
+pub struct Use {
+    pub graph: Path,
+    pub to_nodes: Vec<Edge>,
+    /// Written before `use`, applied to every run of the graph in place.
+    pub annotations: Annotations,
+    pub span: Span,
+}
+
+impl Rebase for Use {
+    fn rebase(&mut self, base: usize) {
+        self.graph.rebase(base);
+        self.to_nodes.rebase(base);
+        self.annotations.rebase(base);
+        self.span.rebase(base);
+    }
+}
+
+impl<'a> Parser<'a> for Use {
+    fn parse(input: Input<'a>) -> IResult<'a, Self> {
+        map(
+            tuple((
+                opt(tuple((Annotations::parse, opt(blank)))),
+                spanned(preceded(
+                    tuple((token("use"), blank)),
+                    cut(tuple((
+                        Path::parse,
+                        opt(blank),
+                        token("->"),
+                        opt(blank),
+                        edges,
+                        opt(blank),
+                        token(";"),
+                    ))),
+                )),
+            )),
+            |(annotations, ((graph, _, _, _, to_nodes, _, _), span))| Use {
+                graph,
+                to_nodes,
+                annotations: annotations.map(|(a, _)| a).unwrap_or_default(),
+                span,
+            },
+        )(input)
+    }
+}
+
+/// A single edge, or a parenthesized list of them, as the successors after `use G ->`.
+pub(super) fn edges(input: Input) -> IResult<Vec<Edge>> {
+    alt((
+        delimited(
+            token("("),
+            many0(map(
+                tuple((opt(blank), Edge::parse, opt(blank), opt(list_separator))),
+                |(_, edge, _, _)| edge,
+            )),
+            tuple((opt(blank), token(")"))),
+        ),
+        map(Edge::parse, |edge| vec![edge]),
+    ))(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_use() {
+        let input = Input::new(
+            r#"#[timeout_ms = "50"]
+        use Profile -> (Rank, ?Log);"#,
+        );
+        match super::Use::parse(input) {
+            Ok((remain, u)) => {
+                assert_eq!(*remain.fragment(), "");
+                assert_eq!(u.graph.to_string(), "Profile");
+                assert_eq!(u.to_nodes.len(), 2);
+                assert!(u.to_nodes[1].soft);
+                assert_eq!(u.annotations[0].key, "timeout_ms");
+                assert_eq!(u.span.start, input.find("use").unwrap());
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+        match super::Use::parse(Input::new("use Profile -> Rank;\nnode Rank {}")) {
+            Ok((remain, u)) => {
+                assert_eq!(*remain.fragment(), "\nnode Rank {}");
+                assert_eq!(u.to_nodes.len(), 1);
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_use_missing_semicolon() {
+        match super::Use::parse(Input::new("use Profile -> Rank {}")) {
+            Err(nom::Err::Failure(e)) => {
+                assert_eq!(e.offset, 20);
+                assert_eq!(e.message(), "expected `;`");
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+}
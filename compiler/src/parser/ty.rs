@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{cut, map, opt},
+    sequence::tuple,
+};
+
+use super::{
+    blank, expect, list_separator, path::Path, token, Expected, IResult, Input, Parser, Rebase,
+};
+
+#[derive(Debug, Clone)]
+pub enum Type {
+    String,
+    Void,
+    Byte,
+    Bool,
+    Binary,
+    I8,
+    I16,
+    I32,
+    I64,
+    Double,
+    List { value: Arc<Type> },
+    Set { value: Arc<Type> },
+    Map { key: Arc<Type>, value: Arc<Type> },
+    Path(Path),
+}
+
+impl Rebase for Type {
+    fn rebase(&mut self, base: usize) {
+        match self {
+            Type::List { value } | Type::Set { value } => Arc::make_mut(value).rebase(base),
+            Type::Map { key, value } => {
+                Arc::make_mut(key).rebase(base);
+                Arc::make_mut(value).rebase(base);
+            }
+            Type::Path(path) => path.rebase(base),
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Parser<'a> for Type {
+    fn parse(input: Input<'a>) -> IResult<'a, Self> {
+        expect(
+            Expected::Label("type"),
+            alt((
+                map(tag("string"), |_| Type::String),
+                map(tag("void"), |_| Type::Void),
+                map(tag("byte"), |_| Type::Byte),
+                map(tag("bool"), |_| Type::Bool),
+                map(tag("binary"), |_| Type::Binary),
+                map(tag("i8"), |_| Type::I8),
+                map(tag("i16"), |_| Type::I16),
+                map(tag("i32"), |_| Type::I32),
+                map(tag("i64"), |_| Type::I64),
+                map(tag("double"), |_| Type::Double),
+                map(
+                    tuple((
+                        tag("list"),
+                        opt(blank),
+                        tag("<"),
+                        cut(tuple((opt(blank), Type::parse, opt(blank), token(">")))),
+                    )),
+                    |(_, _, _, (_, inner_type, _, _))| Type::List {
+                        value: Arc::new(inner_type),
+                    },
+                ),
+                map(
+                    tuple((
+                        tag("set"),
+                        opt(blank),
+                        tag("<"),
+                        cut(tuple((opt(blank), Type::parse, opt(blank), token(">")))),
+                    )),
+                    |(_, _, _, (_, inner_type, _, _))| Type::Set {
+                        value: Arc::new(inner_type),
+                    },
+                ),
+                map(
+                    tuple((
+                        tag("map"),
+                        opt(blank),
+                        tag("<"),
+                        cut(tuple((
+                            opt(blank),
+                            Type::parse,
+                            opt(blank),
+                            list_separator,
+                            opt(blank),
+                            Type::parse,
+                            opt(blank),
+                            token(">"),
+                        ))),
+                    )),
+                    |(_, _, _, (_, key_type, _, _, _, value_type, _, _))| Type::Map {
+                        key: Arc::new(key_type),
+                        value: Arc::new(value_type),
+                    },
+                ),
+                map(Path::parse, Type::Path),
+            )),
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type() {
+        let input = Input::new("string");
+        match super::Type::parse(input) {
+            Ok((remain, ty)) => {
+                assert_eq!(*remain.fragment(), "");
+                match ty {
+                    Type::String => {}
+                    _ => panic!("Expected String"),
+                }
+            }
+            Err(e) => panic!("Error: {e:?}"),
+        }
+    }
+}
@@ -0,0 +1,1673 @@
+pub mod rir;
+
+use std::{str::FromStr, sync::Arc};
+
+use faststr::FastStr;
+use fxhash::{FxHashMap, FxHashSet};
+
+use crate::{
+    diagnostic::{Diagnostic, Level},
+    index::Idx,
+    parser::{document::Document, Span},
+    symbol::{DefId, Ident, Symbol, TagId},
+    tags::Annotation,
+    tags::{Backoff, Concurrency, Construct, Editable, Join, Raw, Retry, Tags, Timeout},
+};
+
+use self::rir::{Field, Graph, Node, NodeKind, Path, Type};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DefKind {
+    Node,
+    Graph,
+    Mod,
+}
+
+impl DefKind {
+    fn describe(self) -> &'static str {
+        match self {
+            DefKind::Node => "node",
+            DefKind::Graph => "graph",
+            DefKind::Mod => "module",
+        }
+    }
+}
+
+/// A name defined in a module.
+struct Def {
+    kind: DefKind,
+    name: Ident,
+    /// The module the name is defined in.
+    module: DefId,
+}
+
+/// The items of a document and of all its `mod` blocks, with the module each is declared in
+/// and the id it was defined with, if its name was free.
+#[derive(Default)]
+struct Items<'a> {
+    nodes: Vec<(DefId, Option<DefId>, &'a crate::parser::node::Node)>,
+    graphs: Vec<(DefId, Option<DefId>, &'a crate::parser::graph::Graph)>,
+    uses: Vec<(DefId, &'a crate::parser::subgraph::Use)>,
+}
+
+pub struct Resolver {
+    graphs: FxHashMap<DefId, Arc<Graph>>,
+    nodes: FxHashMap<DefId, Arc<Node>>,
+    fields: FxHashMap<DefId, Arc<Field>>,
+    tags: FxHashMap<TagId, Arc<Tags>>,
+    did_counter: DefId,
+    tid_counter: TagId,
+    /// Names of the nodes, graphs and modules of each module, which share one namespace,
+    /// keyed by the module.
+    scopes: FxHashMap<DefId, FxHashMap<Symbol, DefId>>,
+    /// The path of each module from the top level of the document.
+    module_paths: FxHashMap<DefId, Path>,
+    /// Names of the fields of each node, keyed by the node.
+    field_scopes: FxHashMap<DefId, FxHashMap<Symbol, DefId>>,
+    defs: FxHashMap<DefId, Def>,
+    /// The node standing for each graph used with `use G`, keyed by the graph.
+    subgraphs: FxHashMap<DefId, DefId>,
+    /// Runs of graphs with successors of their own, lowered once every graph is.
+    use_sites: Vec<UseSite>,
+    /// Where the predicate of each conditional edge is written, keyed by the nodes it joins.
+    condition_spans: FxHashMap<(DefId, DefId), Span>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// A run of a graph whose successors are written on the edge leading to it, `use G -> X`.
+struct UseSite {
+    graph_id: DefId,
+    def_id: DefId,
+    /// The node the edge leaves, qualified by its modules.
+    from: String,
+    /// The module the edge is written in.
+    module: DefId,
+    to_nodes: Vec<crate::parser::node::Edge>,
+    span: Span,
+}
+
+/// The lowered edges leaving a node.
+#[derive(Default)]
+struct Edges {
+    to_nodes: Vec<DefId>,
+    conditions: FxHashMap<DefId, Path>,
+    soft_edges: FxHashSet<DefId>,
+    each_edges: FxHashSet<DefId>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self {
+            graphs: Default::default(),
+            nodes: Default::default(),
+            fields: Default::default(),
+            tags: Default::default(),
+            did_counter: DefId::from_usize(0),
+            tid_counter: TagId::from_usize(0),
+            scopes: Default::default(),
+            module_paths: Default::default(),
+            field_scopes: Default::default(),
+            defs: Default::default(),
+            subgraphs: Default::default(),
+            use_sites: Default::default(),
+            condition_spans: Default::default(),
+            diagnostics: Default::default(),
+        }
+    }
+}
+
+pub struct ResolveResult {
+    pub graphs: FxHashMap<DefId, Arc<Graph>>,
+    pub nodes: FxHashMap<DefId, Arc<Node>>,
+    pub fields: FxHashMap<DefId, Arc<Field>>,
+    pub tags: FxHashMap<TagId, Arc<Tags>>,
+    pub entrys: Vec<DefId>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+impl Resolver {
+    /// Lower `document` and check it describes runnable graphs.
+    ///
+    /// All problems found are returned together; warnings alone do not fail resolution.
+    pub fn resolve_document(
+        mut self,
+        document: Document,
+    ) -> Result<ResolveResult, Vec<Diagnostic>> {
+        let root = self.did_counter.inc_one();
+        self.module_paths.insert(
+            root,
+            Path {
+                segments: Arc::from([]),
+            },
+        );
+        let mut items = Items::default();
+        self.define_items(root, &document, &mut items);
+
+        let mut uses = Vec::with_capacity(items.uses.len());
+        for (module, u) in items.uses {
+            let Some(graph_id) = self.lookup(module, &u.graph, DefKind::Graph) else {
+                continue;
+            };
+            if self.subgraphs.contains_key(&graph_id) {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "the successors of graph `{}` are declared multiple times",
+                            u.graph
+                        ),
+                        u.span,
+                    )
+                    .with_note(format!(
+                        "to continue to different nodes, write them on each edge, as in `node A -> use {} -> B {{}}`",
+                        u.graph
+                    )),
+                );
+                continue;
+            }
+            uses.push((graph_id, self.subgraph_node(graph_id), module, u));
+        }
+
+        for (module, def_id, node) in items.nodes {
+            if let Some(def_id) = def_id {
+                self.lower_node(def_id, module, node);
+            }
+        }
+        let entrys: Vec<_> = items
+            .graphs
+            .into_iter()
+            .filter_map(|(module, def_id, graph)| self.lower_graph(def_id?, module, graph))
+            .collect();
+        for (graph_id, def_id, module, u) in uses {
+            self.lower_use(graph_id, def_id, Some((module, u)));
+        }
+        // Sites may lead to further sites, which are queued as they are lowered.
+        while !self.use_sites.is_empty() {
+            for site in std::mem::take(&mut self.use_sites) {
+                self.lower_use_site(site);
+            }
+        }
+        let mut unused: Vec<_> = self
+            .subgraphs
+            .iter()
+            .filter(|(_, def_id)| !self.nodes.contains_key(def_id))
+            .map(|(graph_id, def_id)| (*graph_id, *def_id))
+            .collect();
+        unused.sort();
+        for (graph_id, def_id) in unused {
+            self.lower_use(graph_id, def_id, None);
+        }
+
+        self.check_cycles();
+        self.check_uses(&entrys);
+        self.check_switches();
+        self.check_joins();
+        self.check_each_edges();
+        self.check_graphs(&entrys);
+
+        let (errors, warnings): (Vec<_>, Vec<_>) = self
+            .diagnostics
+            .into_iter()
+            .partition(|d| d.level == Level::Error);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(ResolveResult {
+            graphs: self.graphs,
+            nodes: self.nodes,
+            fields: self.fields,
+            tags: self.tags,
+            entrys,
+            warnings,
+        })
+    }
+
+    /// Define the names declared in `document`, the body of `module`, and collect its items.
+    fn define_items<'a>(&mut self, module: DefId, document: &'a Document, items: &mut Items<'a>) {
+        for node in &document.nodes {
+            let def_id = self.define(module, &node.name, DefKind::Node);
+            items.nodes.push((module, def_id, node));
+        }
+        for graph in &document.graphs {
+            let def_id = self.define(module, &graph.name, DefKind::Graph);
+            items.graphs.push((module, def_id, graph));
+        }
+        items.uses.extend(document.uses.iter().map(|u| (module, u)));
+        for m in &document.mods {
+            let Some(def_id) = self.define(module, &m.name, DefKind::Mod) else {
+                continue;
+            };
+            let mut path = self.module_paths[&module].segments.to_vec();
+            path.push(self.lower_ident(&m.name));
+            self.module_paths.insert(
+                def_id,
+                Path {
+                    segments: Arc::from(path),
+                },
+            );
+            self.define_items(def_id, &m.items, items);
+        }
+    }
+
+    fn define(
+        &mut self,
+        module: DefId,
+        ident: &crate::parser::ident::Ident,
+        kind: DefKind,
+    ) -> Option<DefId> {
+        let name = self.lower_ident(ident);
+        if let Some(prev) = self
+            .scopes
+            .get(&module)
+            .and_then(|scope| scope.get(&name.sym))
+            .and_then(|def_id| self.defs.get(def_id))
+        {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    format!("the name `{name}` is defined multiple times"),
+                    ident.span(),
+                )
+                .with_note(format!(
+                    "`{name}` must be defined only once, it is already a {}",
+                    prev.kind.describe()
+                )),
+            );
+            return None;
+        }
+        let def_id = self.did_counter.inc_one();
+        self.scopes
+            .entry(module)
+            .or_default()
+            .insert(name.sym.clone(), def_id);
+        self.defs.insert(def_id, Def { kind, name, module });
+        Some(def_id)
+    }
+
+    /// Resolve `path`, written in `module`, to a definition of kind `expected`.
+    ///
+    /// The first segment names something in the innermost enclosing module defining it, and
+    /// every other segment something in the module named before it.
+    fn lookup(
+        &mut self,
+        module: DefId,
+        path: &crate::parser::path::Path,
+        expected: DefKind,
+    ) -> Option<DefId> {
+        let (first, rest) = path.segments.split_first()?;
+        let mut scope = Some(module);
+        let mut def_id = None;
+        while let (Some(module), None) = (scope, def_id) {
+            def_id = self
+                .scopes
+                .get(&module)
+                .and_then(|scope| scope.get(first.0.as_str()))
+                .copied();
+            scope = self.defs.get(&module).map(|def| def.module);
+        }
+        for (i, segment) in rest.iter().enumerate() {
+            let Some(module) = def_id else {
+                break;
+            };
+            let kind = self.defs[&module].kind;
+            if kind != DefKind::Mod {
+                let prefix = path.segments[..=i]
+                    .iter()
+                    .map(|s| s.0.as_str())
+                    .collect::<Vec<_>>()
+                    .join("::");
+                self.diagnostics.push(Diagnostic::error(
+                    format!("expected a module, found {} `{prefix}`", kind.describe()),
+                    path.span,
+                ));
+                return None;
+            }
+            def_id = self
+                .scopes
+                .get(&module)
+                .and_then(|scope| scope.get(segment.0.as_str()))
+                .copied();
+        }
+
+        match def_id.map(|def_id| (def_id, self.defs[&def_id].kind)) {
+            Some((def_id, kind)) if kind == expected => Some(def_id),
+            Some((_, kind)) => {
+                self.diagnostics.push(Diagnostic::error(
+                    format!(
+                        "expected a {}, found {} `{path}`",
+                        expected.describe(),
+                        kind.describe(),
+                    ),
+                    path.span,
+                ));
+                None
+            }
+            None => {
+                self.diagnostics.push(Diagnostic::error(
+                    format!("cannot find {} `{path}`", expected.describe()),
+                    path.span,
+                ));
+                None
+            }
+        }
+    }
+
+    /// The node standing for graph `graph_id` wherever it is used.
+    fn subgraph_node(&mut self, graph_id: DefId) -> DefId {
+        *self
+            .subgraphs
+            .entry(graph_id)
+            .or_insert_with(|| self.did_counter.inc_one())
+    }
+
+    /// Lower `edges`, written in `module`, leaving node `from` named `from_name`.
+    fn lower_edges(
+        &mut self,
+        from: DefId,
+        from_name: &str,
+        module: DefId,
+        edges: &[crate::parser::node::Edge],
+    ) -> Edges {
+        let mut lowered = Edges::default();
+        for edge in edges {
+            let to = if edge.subgraph {
+                self.lookup(module, &edge.to, DefKind::Graph)
+                    .map(|graph_id| match &edge.successors {
+                        Some(to_nodes) => {
+                            let def_id = self.did_counter.inc_one();
+                            self.use_sites.push(UseSite {
+                                graph_id,
+                                def_id,
+                                from: from_name.to_string(),
+                                module,
+                                to_nodes: to_nodes.clone(),
+                                span: edge.to.span,
+                            });
+                            def_id
+                        }
+                        None => self.subgraph_node(graph_id),
+                    })
+            } else {
+                self.lookup(module, &edge.to, DefKind::Node)
+            };
+            let Some(to) = to else {
+                continue;
+            };
+            lowered.to_nodes.push(to);
+            if edge.soft {
+                lowered.soft_edges.insert(to);
+            }
+            if edge.each {
+                lowered.each_edges.insert(to);
+            }
+            if let Some(condition) = &edge.condition {
+                lowered.conditions.insert(to, self.lower_path(condition));
+                self.condition_spans.insert((from, to), condition.span);
+            }
+        }
+        lowered
+    }
+
+    fn lower_node_tags(
+        &mut self,
+        name: &Ident,
+        annotations: &crate::parser::annotations::Annotations,
+    ) -> TagId {
+        let tag_id = self.tid_counter.inc_one();
+        let tags = self.extract_tags(annotations);
+        if tags.contains::<Backoff>() && !tags.contains::<Retry>() {
+            if let Some(backoff) = annotations.iter().find(|a| a.key == Backoff::KEY) {
+                self.diagnostics.push(Diagnostic::warning(
+                    format!("`backoff_ms` has no effect on node `{name}` without `retry`"),
+                    backoff.span,
+                ));
+            }
+        }
+        self.tags.insert(tag_id, tags.into());
+        tag_id
+    }
+
+    fn lower_node(
+        &mut self,
+        def_id: DefId,
+        module: DefId,
+        n: &crate::parser::node::Node,
+    ) -> Arc<Node> {
+        let name = self.lower_ident(&n.name);
+        let qualified = self.qualified_name(module, &name);
+        let edges = self.lower_edges(def_id, &qualified, module, &n.to_nodes);
+        let fields = n
+            .fields
+            .iter()
+            .filter_map(|field| self.lower_field(def_id, &name, field))
+            .collect();
+
+        let resp_ty = n.resp_ty.as_ref().map(|ty| self.lower_type(ty));
+
+        let tag_id = self.lower_node_tags(&name, &n.annotations);
+
+        let kind = match n.kind {
+            crate::parser::node::NodeKind::Node => NodeKind::Node,
+            crate::parser::node::NodeKind::Switch => NodeKind::Switch,
+        };
+
+        let node = Arc::from(Node {
+            kind,
+            name,
+            module: self.module_paths[&module].clone(),
+            to_nodes: edges.to_nodes,
+            conditions: edges.conditions,
+            soft_edges: edges.soft_edges,
+            each_edges: edges.each_edges,
+            resp_ty,
+            fields,
+            tag_id,
+            span: n.span,
+        });
+
+        self.nodes.insert(def_id, node.clone());
+
+        node
+    }
+
+    /// Lower the node standing for graph `graph_id`, with the successors and annotations of
+    /// its `use` item, written in the given module, if it has one.
+    fn lower_use(
+        &mut self,
+        graph_id: DefId,
+        def_id: DefId,
+        u: Option<(DefId, &crate::parser::subgraph::Use)>,
+    ) {
+        // A graph that failed to lower still gets its node, so the edges to it stay valid.
+        let graph = self.graphs.get(&graph_id).cloned();
+        let def = &self.defs[&graph_id];
+        let name = Ident::from(format!("use {}", def.name));
+        let qualified = self.qualified_name(def.module, &name);
+        let module = self.module_paths[&def.module].clone();
+        let (edges, tag_id, span) = match u {
+            Some((use_module, u)) => (
+                self.lower_edges(def_id, &qualified, use_module, &u.to_nodes),
+                self.lower_node_tags(&name, &u.annotations),
+                u.span,
+            ),
+            None => (
+                Edges::default(),
+                self.lower_node_tags(&name, &Default::default()),
+                graph.as_ref().map(|g| g.span).unwrap_or_default(),
+            ),
+        };
+
+        self.nodes.insert(
+            def_id,
+            Arc::from(Node {
+                kind: NodeKind::Graph(graph_id),
+                name,
+                module,
+                to_nodes: edges.to_nodes,
+                conditions: edges.conditions,
+                soft_edges: edges.soft_edges,
+                each_edges: edges.each_edges,
+                resp_ty: graph.and_then(|g| g.resp_ty.clone()),
+                fields: Vec::new(),
+                tag_id,
+                span,
+            }),
+        );
+    }
+
+    /// Lower the node standing for a run of a graph with successors of its own. It is named
+    /// after the node it follows, and takes the annotations of the graph's `use` item.
+    fn lower_use_site(&mut self, site: UseSite) {
+        let graph = self.graphs.get(&site.graph_id).cloned();
+        let def = &self.defs[&site.graph_id];
+        let name = Ident::from(format!("use {} after {}", def.name, site.from));
+        let qualified = self.qualified_name(def.module, &name);
+        let module = self.module_paths[&def.module].clone();
+        let edges = self.lower_edges(site.def_id, &qualified, site.module, &site.to_nodes);
+        let tag_id = match self
+            .subgraphs
+            .get(&site.graph_id)
+            .and_then(|def_id| self.nodes.get(def_id))
+        {
+            Some(node) => node.tag_id,
+            None => self.lower_node_tags(&name, &Default::default()),
+        };
+
+        self.nodes.insert(
+            site.def_id,
+            Arc::from(Node {
+                kind: NodeKind::Graph(site.graph_id),
+                name,
+                module,
+                to_nodes: edges.to_nodes,
+                conditions: edges.conditions,
+                soft_edges: edges.soft_edges,
+                each_edges: edges.each_edges,
+                resp_ty: graph.and_then(|g| g.resp_ty.clone()),
+                fields: Vec::new(),
+                tag_id,
+                span: site.span,
+            }),
+        );
+    }
+
+    /// `name`, declared in `module`, as written from the top level, e.g. `recall::A`.
+    fn qualified_name(&self, module: DefId, name: &Ident) -> String {
+        self.module_paths[&module]
+            .segments
+            .iter()
+            .map(|s| s.to_string())
+            .chain(std::iter::once(name.to_string()))
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    fn lower_graph(
+        &mut self,
+        def_id: DefId,
+        module: DefId,
+        g: &crate::parser::graph::Graph,
+    ) -> Option<DefId> {
+        let name = self.lower_ident(&g.name);
+        let entry_node = self.lookup(module, &g.entry_node, DefKind::Node)?;
+
+        let tag_id = self.tid_counter.inc_one();
+        let tags = self.extract_tags(&g.annotations);
+        self.tags.insert(tag_id, tags.into());
+
+        let graph = Arc::from(Graph {
+            name,
+            module: self.module_paths[&module].clone(),
+            entry_node,
+            req_ty: g.req_ty.as_ref().map(|ty| self.lower_type(ty)),
+            resp_ty: g.resp_ty.as_ref().map(|ty| self.lower_type(ty)),
+            error_ty: g.error_ty.as_ref().map(|ty| self.lower_type(ty)),
+            tag_id,
+            span: g.span,
+        });
+
+        self.graphs.insert(def_id, graph);
+
+        Some(def_id)
+    }
+
+    fn lower_field(
+        &mut self,
+        node_id: DefId,
+        node_name: &Ident,
+        f: &crate::parser::field::Field,
+    ) -> Option<Arc<Field>> {
+        let name = self.lower_ident(&f.name);
+        let scope = self.field_scopes.entry(node_id).or_default();
+        if scope.contains_key(&name.sym) {
+            self.diagnostics.push(Diagnostic::error(
+                format!("field `{name}` is declared multiple times in node `{node_name}`"),
+                f.name.span(),
+            ));
+            return None;
+        }
+        let def_id = self.did_counter.inc_one();
+        scope.insert(name.sym.clone(), def_id);
+
+        let tag_id = self.tid_counter.inc_one();
+        let tags = self.extract_tags(&f.annotations);
+
+        let ty = self.lower_type(&f.ty);
+        let ty = self.modify_ty_by_tags(ty, &tags);
+
+        self.tags.insert(tag_id, tags.into());
+
+        let field = Arc::from(Field {
+            def_id,
+            name,
+            ty,
+            tag_id,
+        });
+
+        self.fields.insert(def_id, field.clone());
+
+        Some(field)
+    }
+
+    /// Report every cycle among the nodes, each with the path that closes it.
+    fn check_cycles(&mut self) {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum Mark {
+            Visiting,
+            Done,
+        }
+
+        fn visit(
+            nodes: &FxHashMap<DefId, Arc<Node>>,
+            def_id: DefId,
+            marks: &mut FxHashMap<DefId, Mark>,
+            path: &mut Vec<DefId>,
+            diagnostics: &mut Vec<Diagnostic>,
+        ) {
+            match marks.get(&def_id) {
+                Some(Mark::Done) => return,
+                Some(Mark::Visiting) => {
+                    let start = path.iter().position(|did| *did == def_id).unwrap();
+                    let cycle = path[start..]
+                        .iter()
+                        .chain(std::iter::once(&def_id))
+                        .map(|did| nodes[did].name.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+                    diagnostics.push(
+                        Diagnostic::error(
+                            format!("node `{}` depends on itself", nodes[&def_id].name),
+                            nodes[&def_id].span,
+                        )
+                        .with_note(format!("cycle: {cycle}")),
+                    );
+                    return;
+                }
+                None => {}
+            }
+            marks.insert(def_id, Mark::Visiting);
+            path.push(def_id);
+            for to in &nodes[&def_id].to_nodes {
+                visit(nodes, *to, marks, path, diagnostics);
+            }
+            path.pop();
+            marks.insert(def_id, Mark::Done);
+        }
+
+        let mut def_ids: Vec<_> = self.nodes.keys().copied().collect();
+        def_ids.sort();
+        let mut marks = FxHashMap::default();
+        for def_id in def_ids {
+            visit(
+                &self.nodes,
+                def_id,
+                &mut marks,
+                &mut Vec::new(),
+                &mut self.diagnostics,
+            );
+        }
+    }
+
+    /// Check every graph used as a node declares the types it runs with, and that no graph
+    /// uses itself, directly or through other graphs.
+    fn check_uses(&mut self, entrys: &[DefId]) {
+        let mut used: Vec<_> = self
+            .nodes
+            .iter()
+            .filter_map(|(def_id, node)| match node.kind {
+                NodeKind::Graph(graph_id) => Some((*def_id, graph_id)),
+                _ => None,
+            })
+            .collect();
+        used.sort();
+        for (def_id, graph_id) in used {
+            let Some(graph) = self.graphs.get(&graph_id) else {
+                continue;
+            };
+            if graph.req_ty.is_none() || graph.resp_ty.is_none() {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "graph `{}` is used as a node and must declare its request and output types",
+                            graph.name
+                        ),
+                        self.nodes[&def_id].span,
+                    )
+                    .with_note(format!("e.g. `graph {}(..): crate::Req -> crate::Resp`", graph.name)),
+                );
+            }
+        }
+
+        // The graphs each graph uses, in the order they are reached.
+        let mut uses: FxHashMap<DefId, Vec<DefId>> = FxHashMap::default();
+        for graph_id in entrys {
+            let graph = self.graphs[graph_id].clone();
+            let mut reachable = FxHashSet::default();
+            let mut stack = vec![graph.entry_node];
+            while let Some(did) = stack.pop() {
+                if !reachable.insert(did) {
+                    continue;
+                }
+                let node = &self.nodes[&did];
+                stack.extend(node.to_nodes.iter().rev().copied());
+                let NodeKind::Graph(used_id) = node.kind else {
+                    continue;
+                };
+                uses.entry(*graph_id).or_default().push(used_id);
+                let Some(used) = self.graphs.get(&used_id) else {
+                    continue;
+                };
+                if used.req_ty.is_some() && graph.req_ty != used.req_ty {
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            format!(
+                                "graph `{}` must declare the same request type as graph `{}` it uses",
+                                graph.name, used.name
+                            ),
+                            graph.span,
+                        )
+                        .with_note(format!("the request is handed to `{}` as is", used.name)),
+                    );
+                }
+                if let (Some(error), Some(used_error)) = (&graph.error_ty, &used.error_ty) {
+                    if error != used_error {
+                        self.diagnostics.push(Diagnostic::error(
+                            format!(
+                                "graph `{}` must declare the same error type as graph `{}` it uses",
+                                graph.name, used.name
+                            ),
+                            graph.span,
+                        ));
+                    }
+                }
+            }
+        }
+
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum Mark {
+            Visiting,
+            Done,
+        }
+
+        fn visit(
+            graphs: &FxHashMap<DefId, Arc<Graph>>,
+            uses: &FxHashMap<DefId, Vec<DefId>>,
+            def_id: DefId,
+            marks: &mut FxHashMap<DefId, Mark>,
+            path: &mut Vec<DefId>,
+            diagnostics: &mut Vec<Diagnostic>,
+        ) {
+            match marks.get(&def_id) {
+                Some(Mark::Done) => return,
+                Some(Mark::Visiting) => {
+                    let start = path.iter().position(|did| *did == def_id).unwrap();
+                    let cycle = path[start..]
+                        .iter()
+                        .chain(std::iter::once(&def_id))
+                        .map(|did| graphs[did].name.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+                    diagnostics.push(
+                        Diagnostic::error(
+                            format!("graph `{}` uses itself", graphs[&def_id].name),
+                            graphs[&def_id].span,
+                        )
+                        .with_note(format!("cycle: {cycle}")),
+                    );
+                    return;
+                }
+                None => {}
+            }
+            marks.insert(def_id, Mark::Visiting);
+            path.push(def_id);
+            for used in uses.get(&def_id).into_iter().flatten() {
+                if graphs.contains_key(used) {
+                    visit(graphs, uses, *used, marks, path, diagnostics);
+                }
+            }
+            path.pop();
+            marks.insert(def_id, Mark::Done);
+        }
+
+        let mut marks = FxHashMap::default();
+        for graph_id in entrys {
+            visit(
+                &self.graphs,
+                &uses,
+                *graph_id,
+                &mut marks,
+                &mut Vec::new(),
+                &mut self.diagnostics,
+            );
+        }
+    }
+
+    /// The nodes with an edge to each node.
+    fn predecessors(&self) -> FxHashMap<DefId, Vec<DefId>> {
+        let mut predecessors: FxHashMap<DefId, Vec<DefId>> = FxHashMap::default();
+        for (did, node) in &self.nodes {
+            for to in &node.to_nodes {
+                predecessors.entry(*to).or_default().push(*did);
+            }
+        }
+        predecessors
+    }
+
+    /// Check nodes joining anything but all of their predecessors are entered by plain edges.
+    fn check_joins(&mut self) {
+        let predecessors = self.predecessors();
+        let mut joins: Vec<_> = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| {
+                self.tags[&node.tag_id]
+                    .get::<Join>()
+                    .is_some_and(|join| *join != Join::All)
+            })
+            .map(|(did, node)| (*did, node.clone()))
+            .collect();
+        joins.sort_by_key(|(did, _)| *did);
+        for (did, node) in joins {
+            let preds = predecessors
+                .get(&did)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            if preds
+                .iter()
+                .any(|pred| self.nodes[pred].conditions.contains_key(&did))
+            {
+                self.diagnostics.push(Diagnostic::error(
+                    format!(
+                        "node `{}` cannot be both joined partially and behind a conditional edge",
+                        node.name
+                    ),
+                    node.span,
+                ));
+            }
+            if preds
+                .iter()
+                .any(|pred| self.nodes[pred].soft_edges.contains(&did))
+            {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "node `{}` cannot be both joined partially and behind a soft edge",
+                            node.name
+                        ),
+                        node.span,
+                    )
+                    .with_note("a partial join already tolerates failed predecessors"),
+                );
+            }
+            let merges_switch = preds.iter().any(|pred| {
+                predecessors.get(pred).is_some_and(|pred_preds| {
+                    pred_preds
+                        .iter()
+                        .any(|did| self.nodes[did].kind == NodeKind::Switch)
+                })
+            });
+            if preds.len() < 2 {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "node `{}` has fewer than 2 predecessors and cannot be joined partially",
+                            node.name
+                        ),
+                        node.span,
+                    )
+                    .with_note("`join` picks which responses of the predecessors a node waits for"),
+                );
+            } else if let Some(Join::Quorum(k)) = self.tags[&node.tag_id].get::<Join>() {
+                if *k > preds.len() {
+                    self.diagnostics.push(Diagnostic::error(
+                        format!(
+                            "node `{}` waits for {k} predecessors but only has {}",
+                            node.name,
+                            preds.len()
+                        ),
+                        node.span,
+                    ));
+                }
+            }
+            if merges_switch {
+                self.diagnostics.push(Diagnostic::error(
+                    format!(
+                        "node `{}` merges a switch and cannot be joined partially",
+                        node.name
+                    ),
+                    node.span,
+                ));
+            }
+        }
+    }
+
+    /// Check a node run for each item of a response has no other predecessor.
+    fn check_each_edges(&mut self) {
+        let predecessors = self.predecessors();
+        let mut edges: Vec<_> = self
+            .nodes
+            .iter()
+            .flat_map(|(from, node)| node.each_edges.iter().map(move |to| (*from, *to)))
+            .collect();
+        edges.sort();
+        for (from_id, to_id) in edges {
+            let (from, to) = (&self.nodes[&from_id], &self.nodes[&to_id]);
+            if predecessors[&to_id].len() > 1 {
+                self.diagnostics.push(Diagnostic::error(
+                    format!(
+                        "node `{}` runs for each item of `{}` and must have no other predecessor",
+                        to.name, from.name
+                    ),
+                    to.span,
+                ));
+            }
+            if from.conditions.contains_key(&to_id) || from.soft_edges.contains(&to_id) {
+                self.diagnostics.push(Diagnostic::error(
+                    format!(
+                        "the edge from `{}` to `{}` cannot be both `each` and conditional or soft",
+                        from.name, to.name
+                    ),
+                    from.span,
+                ));
+            }
+            if to.kind == NodeKind::Switch || from.kind == NodeKind::Switch {
+                self.diagnostics.push(Diagnostic::error(
+                    format!(
+                        "the edge from `{}` to `{}` cannot be `each` as it belongs to a switch",
+                        from.name, to.name
+                    ),
+                    from.span,
+                ));
+            }
+            if matches!(to.kind, NodeKind::Graph(_)) {
+                self.diagnostics.push(Diagnostic::error(
+                    format!(
+                        "the edge from `{}` to `{}` cannot be `each` as a graph runs on the request alone",
+                        from.name, to.name
+                    ),
+                    from.span,
+                ));
+            }
+            if to.to_nodes.is_empty() {
+                self.diagnostics.push(
+                    Diagnostic::error(
+                        format!(
+                            "node `{}` runs for each item of `{}` and cannot be a sink",
+                            to.name, from.name
+                        ),
+                        to.span,
+                    )
+                    .with_note("collect its responses in a node after it"),
+                );
+            }
+        }
+    }
+
+    /// Check every switch forms a diamond: each branch is entered only from the switch and
+    /// leads only to one merge node, which is entered only from the branches.
+    fn check_switches(&mut self) {
+        let predecessors = self.predecessors();
+        let names = |dids: &[DefId]| {
+            let mut names: Vec<_> = dids
+                .iter()
+                .map(|did| format!("`{}`", self.nodes[did].name))
+                .collect();
+            names.sort();
+            names.join(", ")
+        };
+
+        let mut switches: Vec<_> = self
+            .nodes
+            .iter()
+            .filter(|(_, node)| node.kind == NodeKind::Switch)
+            .map(|(did, node)| (*did, node.clone()))
+            .collect();
+        switches.sort_by_key(|(did, _)| *did);
+        let mut diagnostics = Vec::new();
+        for (did, switch) in switches {
+            let error = |message: String| Diagnostic::error(message, switch.span);
+            if switch.to_nodes.len() < 2 {
+                diagnostics.push(error(format!(
+                    "switch `{}` must have at least two branches",
+                    switch.name
+                )));
+                continue;
+            }
+            if !switch.conditions.is_empty() || !switch.soft_edges.is_empty() {
+                diagnostics.push(error(format!(
+                    "the edges of switch `{}` cannot be conditional or soft",
+                    switch.name
+                )));
+            }
+
+            let mut merges = Vec::new();
+            for branch_id in &switch.to_nodes {
+                let branch = &self.nodes[branch_id];
+                if branch.kind == NodeKind::Switch {
+                    diagnostics.push(error(format!(
+                        "branch `{}` of switch `{}` cannot be a switch itself",
+                        branch.name, switch.name
+                    )));
+                }
+                let preds = &predecessors[branch_id];
+                if preds.iter().any(|pred| *pred != did) {
+                    diagnostics.push(
+                        error(format!(
+                            "branch `{}` of switch `{}` must have no other predecessor",
+                            branch.name, switch.name
+                        ))
+                        .with_note(format!("predecessors: {}", names(preds))),
+                    );
+                }
+                match branch.to_nodes.as_slice() {
+                    [merge] if branch.conditions.is_empty() && branch.soft_edges.is_empty() => {
+                        merges.push(*merge)
+                    }
+                    _ => diagnostics.push(error(format!(
+                        "branch `{}` of switch `{}` must lead by a plain edge to exactly one node",
+                        branch.name, switch.name
+                    ))),
+                }
+            }
+            if merges.len() != switch.to_nodes.len() {
+                continue;
+            }
+
+            let merge_id = merges[0];
+            if merges.iter().any(|merge| *merge != merge_id) {
+                diagnostics.push(
+                    error(format!(
+                        "the branches of switch `{}` must all lead to the same node",
+                        switch.name
+                    ))
+                    .with_note(format!("they lead to {}", names(&merges))),
+                );
+                continue;
+            }
+            let preds = &predecessors[&merge_id];
+            if preds.iter().any(|pred| !switch.to_nodes.contains(pred)) {
+                diagnostics.push(
+                    error(format!(
+                        "node `{}` merges the branches of switch `{}` and must have no other predecessor",
+                        self.nodes[&merge_id].name, switch.name
+                    ))
+                    .with_note(format!("predecessors: {}", names(preds))),
+                );
+            }
+        }
+        self.diagnostics.extend(diagnostics);
+    }
+
+    /// Check every graph has exactly one sink and every node belongs to some graph.
+    fn check_graphs(&mut self, entrys: &[DefId]) {
+        let mut reachable_from_any = FxHashSet::default();
+        for def_id in entrys {
+            let graph = self.graphs[def_id].clone();
+            let mut reachable = FxHashSet::default();
+            let mut stack = vec![graph.entry_node];
+            let mut sinks = Vec::new();
+            while let Some(did) = stack.pop() {
+                if !reachable.insert(did) {
+                    continue;
+                }
+                let node = &self.nodes[&did];
+                if node.to_nodes.is_empty() {
+                    sinks.push(did);
+                }
+                stack.extend(node.to_nodes.iter().copied());
+            }
+            sinks.sort();
+            if graph.req_ty.is_none() {
+                self.check_untyped_conditions(&graph, &reachable);
+            }
+            match sinks.as_slice() {
+                [sink_id] => {
+                    let sink = &self.nodes[sink_id];
+                    let mut guards: Vec<_> = reachable
+                        .iter()
+                        .filter(|did| self.nodes[*did].conditions.contains_key(sink_id))
+                        .copied()
+                        .collect();
+                    guards.sort();
+                    for did in guards {
+                        let from = &self.nodes[&did];
+                        self.diagnostics.push(
+                            Diagnostic::error(
+                                format!(
+                                    "the edge from `{}` to the sink node `{}` of graph `{}` cannot be conditional",
+                                    from.name, sink.name, graph.name
+                                ),
+                                from.span,
+                            )
+                            .with_note("the graph returns whatever its sink node responds with, so it must always run"),
+                        );
+                    }
+                    if let (Some(out), Some(resp)) = (&graph.resp_ty, &sink.resp_ty) {
+                        if out != resp {
+                            self.diagnostics.push(
+                                Diagnostic::error(
+                                    format!(
+                                        "the output type of graph `{}` differs from the response type of its sink node `{}`",
+                                        graph.name, sink.name
+                                    ),
+                                    graph.span,
+                                )
+                                .with_note("the graph returns whatever its sink node responds with"),
+                            );
+                        }
+                    }
+                }
+                [] => self.diagnostics.push(Diagnostic::error(
+                    format!("graph `{}` has no sink node", graph.name),
+                    graph.span,
+                )),
+                sinks => {
+                    let names = sinks
+                        .iter()
+                        .map(|did| format!("`{}`", self.nodes[did].name))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    self.diagnostics.push(
+                        Diagnostic::error(
+                            format!("graph `{}` has more than one sink node", graph.name),
+                            graph.span,
+                        )
+                        .with_note(format!("sink nodes: {names}"))
+                        .with_note("a graph must end in exactly one node that produces its output"),
+                    );
+                }
+            }
+            reachable_from_any.extend(reachable);
+        }
+
+        let mut unreachable: Vec<_> = self
+            .nodes
+            .keys()
+            .filter(|did| !reachable_from_any.contains(did))
+            .copied()
+            .collect();
+        unreachable.sort();
+        for did in unreachable {
+            let node = &self.nodes[&did];
+            self.diagnostics.push(Diagnostic::warning(
+                format!("node `{}` is not reachable from any graph", node.name),
+                node.span,
+            ));
+        }
+    }
+
+    /// Check graph `graph`, which leaves its request type out, has no conditional edges among
+    /// the nodes in `reachable`: its `run` is generic over the types a predicate is called with.
+    fn check_untyped_conditions(&mut self, graph: &Graph, reachable: &FxHashSet<DefId>) {
+        let mut edges: Vec<_> = reachable
+            .iter()
+            .flat_map(|from| {
+                self.nodes[from]
+                    .conditions
+                    .keys()
+                    .map(move |to| (*from, *to))
+            })
+            .collect();
+        edges.sort();
+        for (from, to) in edges {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    format!(
+                        "the edge from `{}` to `{}` cannot be conditional in graph `{}`, which does not declare its request type",
+                        self.nodes[&from].name, self.nodes[&to].name, graph.name
+                    ),
+                    self.condition_spans[&(from, to)],
+                )
+                .with_note(format!(
+                    "the predicate is called with the request and the response of `{}`, which are generic parameters of `run` unless the graph is declared as `graph {}(..): Request`",
+                    self.nodes[&from].name, graph.name
+                )),
+            );
+        }
+    }
+
+    fn modify_ty_by_tags(&mut self, ty: Type, tags: &Tags) -> Type {
+        if let Some(Editable(true)) = tags.get::<Editable>() {
+            Type::ArcSwap(Arc::from(ty))
+        } else {
+            ty
+        }
+    }
+
+    fn extract_tags(&mut self, annotations: &crate::parser::annotations::Annotations) -> Tags {
+        let mut tags = Tags::default();
+        macro_rules! with_tags {
+            ($annotation: tt -> $($key: ty)|+) => {
+                match $annotation.key.as_str()  {
+                    $(<$key>::KEY => {
+                        match <$key>::from_str(&$annotation.value) {
+                            Ok(value) => tags.insert(value),
+                            Err(e) => self.diagnostics.push(Diagnostic::error(
+                                format!(
+                                    "invalid value `{}` for annotation `{}`: {e}",
+                                    &*$annotation.value, $annotation.key
+                                ),
+                                $annotation.value.1,
+                            )),
+                        }
+                    }),+
+                    _ => {},
+                }
+            };
+        }
+
+        for annotation in annotations.iter() {
+            with_tags!(annotation -> Construct | Editable | Timeout | Retry | Backoff | Join | Concurrency);
+        }
+        tags.insert(Raw(annotations
+            .iter()
+            .map(|a| (FastStr::new(&a.key), FastStr::new(&a.value.0)))
+            .collect()));
+
+        tags
+    }
+
+    fn lower_type(&mut self, ty: &crate::parser::ty::Type) -> Type {
+        match ty {
+            crate::parser::ty::Type::String => Type::String,
+            crate::parser::ty::Type::Void => Type::Void,
+            crate::parser::ty::Type::Byte => Type::U8,
+            crate::parser::ty::Type::Bool => Type::Bool,
+            crate::parser::ty::Type::Binary => Type::Bytes,
+            crate::parser::ty::Type::I8 => Type::I8,
+            crate::parser::ty::Type::I16 => Type::I16,
+            crate::parser::ty::Type::I32 => Type::I32,
+            crate::parser::ty::Type::I64 => Type::I64,
+            crate::parser::ty::Type::Double => Type::F64,
+            crate::parser::ty::Type::List { value } => Type::Vec(Arc::from(self.lower_type(value))),
+            crate::parser::ty::Type::Set { value } => Type::Set(Arc::from(self.lower_type(value))),
+            crate::parser::ty::Type::Map { key, value } => Type::Map(
+                Arc::from(self.lower_type(key)),
+                Arc::from(self.lower_type(value)),
+            ),
+            crate::parser::ty::Type::Path(path) => Type::Path(self.lower_path(path)),
+        }
+    }
+
+    fn lower_ident(&mut self, ident: &crate::parser::ident::Ident) -> Ident {
+        Ident::from(ident.0.clone())
+    }
+
+    fn lower_path(&mut self, path: &crate::parser::path::Path) -> Path {
+        Path {
+            segments: Arc::from_iter(path.segments.iter().map(|i| self.lower_ident(i))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::document::parse_document;
+
+    fn resolve(src: &str) -> Result<ResolveResult, Vec<Diagnostic>> {
+        Resolver::default().resolve_document(parse_document(src).unwrap())
+    }
+
+    fn messages(src: &str) -> Vec<String> {
+        match resolve(src) {
+            Ok(_) => panic!("expected errors"),
+            Err(errors) => errors.into_iter().map(|d| d.message).collect(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_document() {
+        let res = resolve(
+            r#"
+            node E -> (X, Y) {}
+            node X -> O {}
+            node Y -> O {}
+            node O {}
+            graph G(E)
+            "#,
+        );
+        match res {
+            Ok(res) => {
+                assert_eq!(res.nodes.len(), 4);
+                assert_eq!(res.entrys.len(), 1);
+                assert!(res.warnings.is_empty());
+            }
+            Err(e) => panic!("{e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_undefined_node() {
+        assert_eq!(
+            messages("node A -> Typo {}\ngraph G(Missing)"),
+            ["cannot find node `Typo`", "cannot find node `Missing`"]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        assert_eq!(
+            messages("node A {}\nnode A {}\ngraph G(A)\ngraph G(A)"),
+            [
+                "the name `A` is defined multiple times",
+                "the name `G` is defined multiple times"
+            ]
+        );
+        assert_eq!(
+            messages("node A {}\ngraph A(A)"),
+            ["the name `A` is defined multiple times"]
+        );
+        assert_eq!(
+            messages("node A { x: i32, x: string }\ngraph G(A)"),
+            ["field `x` is declared multiple times in node `A`"]
+        );
+    }
+
+    #[test]
+    fn test_field_scopes() {
+        let res = match resolve("node A -> B { b: i32, a: i32 }\nnode B { b: string }\ngraph G(A)")
+        {
+            Ok(res) => res,
+            Err(e) => panic!("{e:?}"),
+        };
+        assert_eq!(res.fields.len(), 3);
+        for node in res.nodes.values() {
+            for field in &node.fields {
+                assert_eq!(res.fields[&field.def_id], *field);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cycle() {
+        let errors =
+            resolve("node A -> B {}\nnode B -> C {}\nnode C -> A, D {}\nnode D {}\ngraph G(A)")
+                .err()
+                .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "node `A` depends on itself");
+        assert_eq!(errors[0].notes, ["cycle: A -> B -> C -> A"]);
+    }
+
+    #[test]
+    fn test_sinks() {
+        assert_eq!(
+            messages("node A -> (B, C) {}\nnode B {}\nnode C {}\ngraph G(A)"),
+            ["graph `G` has more than one sink node"]
+        );
+    }
+
+    #[test]
+    fn test_unreachable_node() {
+        match resolve("node A {}\nnode B {}\ngraph G(A)") {
+            Ok(res) => {
+                assert_eq!(res.warnings.len(), 1);
+                assert_eq!(
+                    res.warnings[0].message,
+                    "node `B` is not reachable from any graph"
+                );
+            }
+            Err(e) => panic!("{e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_node_tags() {
+        let res = match resolve("#[timeout_ms = \"50\"]\nnode A {}\ngraph G(A)") {
+            Ok(res) => res,
+            Err(e) => panic!("{e:?}"),
+        };
+        let node = res.nodes.values().next().unwrap();
+        let tags = &res.tags[&node.tag_id];
+        assert_eq!(
+            tags.get::<Timeout>().map(|t| t.0),
+            Some(std::time::Duration::from_millis(50))
+        );
+
+        assert_eq!(
+            messages("#[timeout_ms = \"soon\"]\nnode A {}\ngraph G(A)"),
+            ["invalid value `soon` for annotation `timeout_ms`: invalid digit found in string"]
+        );
+    }
+
+    #[test]
+    fn test_backoff_without_retry() {
+        match resolve("#[backoff_ms = \"10\"]\nnode A {}\ngraph G(A)") {
+            Ok(res) => {
+                assert_eq!(res.warnings.len(), 1);
+                assert_eq!(
+                    res.warnings[0].message,
+                    "`backoff_ms` has no effect on node `A` without `retry`"
+                );
+            }
+            Err(e) => panic!("{e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_graph_tags() {
+        let res = match resolve("#[owner = \"search\"]\ngraph G(A)\nnode A {}") {
+            Ok(res) => res,
+            Err(e) => panic!("{e:?}"),
+        };
+        let graph = res.graphs.values().next().unwrap();
+        let node = res.nodes.values().next().unwrap();
+        assert_ne!(graph.tag_id, node.tag_id);
+        assert_eq!(res.tags[&graph.tag_id].raw("owner"), Some("search"));
+        assert_eq!(res.tags[&node.tag_id].raw("owner"), None);
+    }
+
+    #[test]
+    fn test_conditional_sink() {
+        assert_eq!(
+            messages("node A -> B if crate::p {}\nnode B {}\ngraph G(A): crate::Req"),
+            ["the edge from `A` to the sink node `B` of graph `G` cannot be conditional"]
+        );
+    }
+
+    #[test]
+    fn test_untyped_condition() {
+        let src = "node A -> (B if crate::p, C) {}\nnode B -> C {}\nnode C {}";
+        if let Err(e) = resolve(&format!("{src}\ngraph G(A): crate::Req")) {
+            panic!("{e:?}");
+        }
+        let src = format!("{src}\ngraph G(A)");
+        let diagnostics = resolve(&src).err().unwrap();
+        assert_eq!(
+            diagnostics.iter().map(|d| &*d.message).collect::<Vec<_>>(),
+            ["the edge from `A` to `B` cannot be conditional in graph `G`, which does not declare its request type"]
+        );
+        let span = diagnostics[0].span;
+        assert_eq!(&src[span.start..span.end], "crate::p");
+    }
+
+    #[test]
+    fn test_switch() {
+        let src = "switch S -> (A, B) {}\nnode A -> M {}\nnode B -> M {}\nnode M {}\ngraph G(S)";
+        if let Err(e) = resolve(src) {
+            panic!("{e:?}");
+        }
+        assert_eq!(
+            messages("switch S -> A {}\nnode A {}\ngraph G(S)"),
+            ["switch `S` must have at least two branches"]
+        );
+        assert_eq!(
+            messages(
+                "node E -> (S, B) {}\nswitch S -> (A, B) {}\nnode A -> M {}\nnode B -> M {}\nnode M {}\ngraph G(E)"
+            ),
+            ["branch `B` of switch `S` must have no other predecessor"]
+        );
+        assert_eq!(
+            messages(
+                "switch S -> (A, B) {}\nnode A -> M {}\nnode B -> N {}\nnode M -> N {}\nnode N {}\ngraph G(S)"
+            ),
+            ["the branches of switch `S` must all lead to the same node"]
+        );
+        assert_eq!(
+            messages(
+                "node E -> (S, M) {}\nswitch S -> (A, B) {}\nnode A -> M {}\nnode B -> M {}\nnode M {}\ngraph G(E)"
+            ),
+            ["node `M` merges the branches of switch `S` and must have no other predecessor"]
+        );
+    }
+
+    #[test]
+    fn test_join() {
+        let src = "node A -> (B, C) {}\nnode B -> D {}\nnode C -> D {}\n#[join = \"first\"]\nnode D {}\ngraph G(A)";
+        if let Err(e) = resolve(src) {
+            panic!("{e:?}");
+        }
+        assert_eq!(
+            messages("node A -> (B, C) {}\nnode B -> D if p {}\nnode C -> D {}\n#[join = \"first\"]\nnode D -> E {}\nnode E {}\ngraph G(A): crate::Req"),
+            ["node `D` cannot be both joined partially and behind a conditional edge"]
+        );
+        assert_eq!(
+            messages("node A -> (B, C) {}\nnode B -> ?D {}\nnode C -> D {}\n#[join = \"first\"]\nnode D {}\ngraph G(A)"),
+            ["node `D` cannot be both joined partially and behind a soft edge"]
+        );
+        assert_eq!(
+            messages("#[join = \"any\"]\nnode A {}\ngraph G(A)"),
+            ["invalid value `any` for annotation `join`: expected `all`, `first` or `quorum(k)`"]
+        );
+        assert_eq!(
+            messages("node A -> (B, C) {}\nnode B -> D {}\nnode C -> D {}\n#[join = \"quorum(3)\"]\nnode D {}\ngraph G(A)"),
+            ["node `D` waits for 3 predecessors but only has 2"]
+        );
+        assert_eq!(
+            messages("#[join = \"first\"]\nnode A -> B {}\nnode B {}\ngraph G(A)"),
+            ["node `A` has fewer than 2 predecessors and cannot be joined partially"]
+        );
+        assert_eq!(
+            messages("node A -> B {}\n#[join = \"quorum(1)\"]\nnode B {}\ngraph G(A)"),
+            ["node `B` has fewer than 2 predecessors and cannot be joined partially"]
+        );
+        assert_eq!(
+            messages("#[join = \"quorum(0)\"]\nnode A {}\ngraph G(A)"),
+            ["invalid value `quorum(0)` for annotation `join`: a quorum must be at least 1"]
+        );
+    }
+
+    #[test]
+    fn test_each_edges() {
+        if let Err(e) = resolve("node A -> each B {}\nnode B -> C {}\nnode C {}\ngraph G(A)") {
+            panic!("{e:?}");
+        }
+        assert_eq!(
+            messages(
+                "node A -> (each B, C) {}\nnode C -> B {}\nnode B -> D {}\nnode D {}\ngraph G(A)"
+            ),
+            ["node `B` runs for each item of `A` and must have no other predecessor"]
+        );
+        assert_eq!(
+            messages("node A -> each B {}\nnode B {}\ngraph G(A)"),
+            ["node `B` runs for each item of `A` and cannot be a sink"]
+        );
+    }
+
+    #[test]
+    fn test_uses() {
+        let profile = "node L {}\ngraph P(L): crate::Req -> crate::Resp\n";
+        match resolve(&format!(
+            "{profile}use P -> O;\nnode M -> (use P, X) {{}}\nnode X -> O {{}}\nnode O {{}}\ngraph G(M): crate::Req"
+        )) {
+            Ok(res) => {
+                let node = res.nodes.values().find(|n| n.name.to_string() == "use P").unwrap();
+                assert!(matches!(node.kind, NodeKind::Graph(_)));
+                assert_eq!(node.to_nodes.len(), 1);
+                assert!(res.warnings.is_empty());
+            }
+            Err(e) => panic!("{e:?}"),
+        }
+        assert_eq!(
+            messages("node L {}\ngraph P(L)\nnode M -> use P {}\ngraph G(M)"),
+            ["graph `P` is used as a node and must declare its request and output types"]
+        );
+        assert_eq!(
+            messages(&format!(
+                "{profile}node M -> use P {{}}\ngraph G(M): crate::Other"
+            )),
+            ["graph `G` must declare the same request type as graph `P` it uses"]
+        );
+        assert_eq!(
+            messages("node M -> (use Missing, use M) {}\ngraph G(M)"),
+            [
+                "cannot find graph `Missing`",
+                "expected a graph, found node `M`"
+            ]
+        );
+        assert_eq!(
+            messages(&format!("{profile}use P -> ();\nuse P -> ();")),
+            ["the successors of graph `P` are declared multiple times"]
+        );
+    }
+
+    #[test]
+    fn test_use_sites() {
+        let src = "node L {}\ngraph P(L): crate::Req -> crate::Resp\n\
+                   #[retry = \"1\"]\nuse P -> ();\n\
+                   node A -> use P -> RankA {}\nnode RankA {}\ngraph GA(A): crate::Req\n\
+                   node B -> (use P -> RankB, X) {}\nnode X -> RankB {}\nnode RankB {}\ngraph GB(B): crate::Req";
+        let res = match resolve(src) {
+            Ok(res) => res,
+            Err(e) => panic!("{e:?}"),
+        };
+        let mut sites: Vec<_> = res
+            .nodes
+            .values()
+            .filter(|n| n.name.to_string().contains(" after "))
+            .collect();
+        sites.sort_by_key(|n| n.name.to_string());
+        let names: Vec<_> = sites.iter().map(|n| n.name.to_string()).collect();
+        assert_eq!(names, ["use P after A", "use P after B"]);
+        for site in sites {
+            assert!(matches!(site.kind, NodeKind::Graph(_)));
+            assert_eq!(site.to_nodes.len(), 1);
+            assert!(res.tags[&site.tag_id].contains::<Retry>());
+        }
+        assert_eq!(
+            res.warnings.iter().map(|w| &*w.message).collect::<Vec<_>>(),
+            ["node `use P` is not reachable from any graph"]
+        );
+        assert_eq!(
+            messages("node L {}\ngraph P(L)\nnode M -> use P -> O {}\nnode O {}\ngraph G(M)"),
+            ["graph `P` is used as a node and must declare its request and output types"]
+        );
+    }
+
+    #[test]
+    fn test_recursive_uses() {
+        let errors = resolve(
+            "node A -> use H {}\ngraph G(A): crate::Req -> crate::Resp\n\
+             node B -> use G {}\ngraph H(B): crate::Req -> crate::Resp",
+        )
+        .err()
+        .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "graph `G` uses itself");
+        assert_eq!(errors[0].notes, ["cycle: G -> H -> G"]);
+    }
+
+    #[test]
+    fn test_modules() {
+        let res = match resolve(
+            "node E -> (recall::A, rank::A) {}\n\
+             mod recall { node A -> O {} }\n\
+             mod rank { node A -> O {} }\n\
+             node O {}\n\
+             mod api { graph G(E) }",
+        ) {
+            Ok(res) => res,
+            Err(e) => panic!("{e:?}"),
+        };
+        assert_eq!(res.nodes.len(), 4);
+        let module = |path: &Path| -> Vec<String> {
+            path.segments.iter().map(|s| s.0.to_string()).collect()
+        };
+        let graph = res.graphs.values().next().unwrap();
+        assert_eq!(module(&graph.module), ["api"]);
+        let mut modules: Vec<_> = res
+            .nodes
+            .values()
+            .filter(|n| n.name.0 == "A")
+            .map(|n| module(&n.module))
+            .collect();
+        modules.sort();
+        assert_eq!(modules, [["rank"], ["recall"]]);
+
+        assert_eq!(
+            messages("node A {}\nmod m { node A {} node A {} }\ngraph G(A)"),
+            ["the name `A` is defined multiple times"]
+        );
+        assert_eq!(
+            messages("node A -> A::B {}\nnode O {}\ngraph G(A)\nmod m { graph H(G) }"),
+            [
+                "expected a module, found node `A`",
+                "expected a node, found graph `G`"
+            ]
+        );
+        assert_eq!(
+            messages("node A -> m::O {}\nmod m { mod n { node O {} } }\ngraph G(A)"),
+            ["cannot find node `m::O`"]
+        );
+    }
+}
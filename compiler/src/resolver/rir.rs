@@ -1,25 +1,58 @@
 use std::sync::Arc;
 
+use fxhash::{FxHashMap, FxHashSet};
+
 use crate::{
     codegen::ty::{Adt, CodegenTy},
+    parser::Span,
     symbol::{DefId, Ident, TagId},
 };
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Graph {
     pub name: Ident,
+    /// The `mod` blocks the graph is declared in, outermost first.
+    pub module: Path,
     pub entry_node: DefId,
+    pub req_ty: Option<Type>,
+    pub resp_ty: Option<Type>,
+    pub error_ty: Option<Type>,
+    pub tag_id: TagId,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NodeKind {
+    Node,
+    /// Its response picks which one of `to_nodes` runs; every branch leads to one merge node.
+    Switch,
+    /// A graph used as a node with `use G`; its response is the output of the graph.
+    Graph(DefId),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Node {
+    pub kind: NodeKind,
     pub name: Ident,
+    /// The `mod` blocks the node is declared in, outermost first.
+    pub module: Path,
     pub to_nodes: Vec<DefId>,
+    /// Predicates guarding the edges to some of `to_nodes`, keyed by the successor.
+    pub conditions: FxHashMap<DefId, Path>,
+    /// Successors that run even when this node fails, receiving its `Result`.
+    pub soft_edges: FxHashSet<DefId>,
+    /// Successors run once per item of this node's response, each run receiving one item;
+    /// the nodes after them receive their responses as a `Vec`.
+    pub each_edges: FxHashSet<DefId>,
+    pub resp_ty: Option<Type>,
     pub fields: Vec<Arc<Field>>,
+    pub tag_id: TagId,
+    pub span: Span,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Field {
+    pub def_id: DefId,
     pub name: Ident,
     pub ty: Type,
     pub tag_id: TagId,
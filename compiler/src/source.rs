@@ -0,0 +1,503 @@
+use std::path::{Path, PathBuf};
+
+use fxhash::{FxHashMap, FxHashSet};
+use heck::ToSnakeCase;
+
+use crate::{
+    diagnostic::{Diagnostic, Report},
+    error::{Error, Result},
+    parser::{
+        document::{parse_document, Document},
+        ident::Ident,
+        module::Module,
+        Rebase, Span,
+    },
+};
+
+/// A file a document was read from.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub src: String,
+    /// Offset of the first byte of the file in the spans of the document.
+    pub base: usize,
+}
+
+/// The files a document was read from, each given its own range of span offsets.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// Add a file and return the offset its spans start at.
+    pub fn add(&mut self, path: PathBuf, src: String) -> usize {
+        let base = self.next_base();
+        self.files.push(SourceFile { path, src, base });
+        base
+    }
+
+    /// The offset the spans of the next file added start at.
+    fn next_base(&self) -> usize {
+        // Files are a byte apart, so a span at the end of one is not the start of the next.
+        self.files
+            .last()
+            .map_or(0, |file| file.base + file.src.len() + 1)
+    }
+
+    /// Resolve `diagnostic` against the file its span falls in.
+    pub fn report(&self, diagnostic: &Diagnostic) -> Report {
+        let Some(file) = self
+            .files
+            .iter()
+            .rev()
+            .find(|file| file.base <= diagnostic.span.start)
+        else {
+            return diagnostic.report("", "");
+        };
+        let span = Span::new(
+            diagnostic.span.start - file.base,
+            diagnostic.span.end - file.base,
+        );
+        Diagnostic {
+            span,
+            ..diagnostic.clone()
+        }
+        .report(&file.path.display().to_string(), &file.src)
+    }
+}
+
+/// Read `path` and every file it imports, transitively, into a single document.
+///
+/// A file imported by several files is read once per call, and a file importing itself,
+/// directly or through other files, is an error.
+pub fn load(path: impl AsRef<Path>) -> Result<(Document, SourceMap)> {
+    load_all(&[path.as_ref().to_path_buf()], false)
+}
+
+/// Read each of `paths` and the files they import into a single document.
+///
+/// With `modules`, the items of every path are put in a `mod` named after the file, so each
+/// file has a namespace of its own and refers to the items of another through its module.
+/// The files they import are read once into the top level, where every module sees them, and
+/// a path cannot be imported by another. Otherwise every item shares one namespace, as if the
+/// paths were imported by one file.
+pub fn load_all(paths: &[PathBuf], modules: bool) -> Result<(Document, SourceMap)> {
+    let mut loader = Loader::default();
+    if modules {
+        loader.modules = paths
+            .iter()
+            .map(|path| (canonical(path), module_name(path)))
+            .collect();
+    }
+    let mut mods = Vec::new();
+    for path in paths {
+        if loader.loaded.contains(&canonical(path)) {
+            continue;
+        }
+        let src = std::fs::read_to_string(path)?;
+        let len = src.len();
+        let base = loader.sources.next_base();
+        let items = loader.load(path.clone(), src, &mut Vec::new())?;
+        if !modules {
+            loader.document.append(items);
+            continue;
+        }
+
+        let span = Span::new(base, base);
+        let name = module_name(path);
+        if syn::parse_str::<syn::Ident>(&name).is_err() {
+            return Err(loader.error(
+                Diagnostic::error(
+                    format!("cannot name a module after `{}`", path.display()),
+                    span,
+                )
+                .with_note(format!("`{name}` is not a valid Rust module name")),
+            ));
+        }
+        mods.push(Module {
+            name: Ident(name.into(), span),
+            items,
+            span: Span::new(base, base + len),
+        });
+    }
+    loader.document.mods.extend(mods);
+    Ok((loader.document, loader.sources))
+}
+
+/// The name of the module the items of the file at `path` are put in by `load_all`.
+fn module_name(path: &Path) -> String {
+    path.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_snake_case()
+}
+
+/// The graph files `path` stands for: the `.graph` files of a directory, the files of a
+/// directory whose name matches a pattern of `*` and `?` wildcards, or else `path` itself.
+pub fn graph_files(path: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let (dir, pattern) = if path.is_dir() {
+        (path, "*.graph")
+    } else if name.contains(['*', '?']) {
+        (path.parent().unwrap_or(Path::new("")), &*name)
+    } else {
+        return Ok(vec![path.to_path_buf()]);
+    };
+
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file()
+            && wildcard_match(pattern, &entry.file_name().to_string_lossy())
+        {
+            files.push(entry.path());
+        }
+    }
+    if files.is_empty() {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("`{}` matches no graph files", path.display()),
+        )));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any run of characters and `?` any
+/// single character.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // The position after the last `*` seen and the position in `name` it resumes from.
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Read `src` into a document, reporting against `name` as its file name.
+///
+/// There is no file to resolve imports against, so a document read from a string cannot
+/// import other files.
+pub fn load_str(name: &str, src: &str) -> Result<(Document, SourceMap)> {
+    let document = parse_document(src).map_err(|d| Error::Parse(Box::new(d.report(name, src))))?;
+    let mut sources = SourceMap::default();
+    sources.add(name.into(), src.to_string());
+    if let Some(import) = document.imports.first() {
+        return Err(Error::Resolve(vec![sources.report(&Diagnostic::error(
+            "cannot import files into a graph read from a string",
+            import.span,
+        ))]));
+    }
+    Ok((document, sources))
+}
+
+#[derive(Default)]
+struct Loader {
+    /// The items of the files read so far that were not put in a module.
+    document: Document,
+    sources: SourceMap,
+    loaded: FxHashSet<PathBuf>,
+    /// The files read into a module of their own, keyed by their canonical path.
+    modules: FxHashMap<PathBuf, String>,
+}
+
+impl Loader {
+    /// Load the file at `path`, whose importers are `stack`, and return its items. The files
+    /// it imports are loaded first, into `document`.
+    fn load(&mut self, path: PathBuf, src: String, stack: &mut Vec<PathBuf>) -> Result<Document> {
+        let file = path.display().to_string();
+        let mut document =
+            parse_document(&src).map_err(|d| Error::Parse(Box::new(d.report(&file, &src))))?;
+        let base = self.sources.add(path.clone(), src);
+        document.rebase(base);
+        self.loaded.insert(canonical(&path));
+        stack.push(path.clone());
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for import in &document.imports {
+            let imported = dir.join(&*import.path);
+            let key = canonical(&imported);
+            if let Some(start) = stack.iter().position(|p| canonical(p) == key) {
+                let cycle = stack[start..]
+                    .iter()
+                    .chain(std::iter::once(&imported))
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                return Err(self.error(
+                    Diagnostic::error(
+                        format!("`{}` imports itself", stack[start].display()),
+                        import.span,
+                    )
+                    .with_note(format!("cycle: {cycle}")),
+                ));
+            }
+            if let Some(name) = self.modules.get(&key) {
+                return Err(self.error(
+                    Diagnostic::error(
+                        format!(
+                            "cannot import `{}`, which is compiled into module `{name}`",
+                            imported.display()
+                        ),
+                        import.span,
+                    )
+                    .with_note(format!("refer to its items as `{name}::Name` instead")),
+                ));
+            }
+            if self.loaded.contains(&key) {
+                continue;
+            }
+            let src = std::fs::read_to_string(&imported).map_err(|e| {
+                self.error(Diagnostic::error(
+                    format!("cannot read `{}`: {e}", imported.display()),
+                    import.path.1,
+                ))
+            })?;
+            let items = self.load(imported, src, stack)?;
+            self.document.append(items);
+        }
+        stack.pop();
+        Ok(document)
+    }
+
+    fn error(&self, diagnostic: Diagnostic) -> Error {
+        Error::Resolve(vec![self.sources.report(&diagnostic)])
+    }
+}
+
+/// The path `path` is known by, so files imported by different relative paths are read once.
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("static-graph-{name}-{}", std::process::id()));
+        for (path, src) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, src).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = write_files(
+            "load",
+            &[
+                (
+                    "main.graph",
+                    "import \"common/a.graph\";\nimport \"common/b.graph\";\nnode E -> (A, B) {}\ngraph G(E)",
+                ),
+                ("common/a.graph", "import \"b.graph\";\nnode A -> O {}"),
+                ("common/b.graph", "node B -> O {}\nnode O {}"),
+            ],
+        );
+        let (document, sources) = load(dir.join("main.graph")).unwrap();
+        assert_eq!(document.nodes.len(), 4);
+        assert_eq!(sources.files().len(), 3);
+
+        let o = document.nodes.iter().find(|n| n.name.0 == "O").unwrap();
+        let report = sources.report(&Diagnostic::error("", o.span));
+        assert!(report.file.ends_with("b.graph"));
+        assert_eq!((report.line, report.column), (2, 1));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_str() {
+        let (document, _) = load_str("<input>", "node A {}\ngraph G(A)").unwrap();
+        assert_eq!(document.nodes.len(), 1);
+        match load_str("<input>", "import \"a.graph\";\nnode A {}") {
+            Err(Error::Resolve(reports)) => {
+                assert_eq!(
+                    reports[0].message,
+                    "cannot import files into a graph read from a string"
+                );
+                assert_eq!(reports[0].file, "<input>");
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_load_all() {
+        let dir = write_files(
+            "load-all",
+            &[
+                ("search.graph", "node E -> O {}\nnode O {}\ngraph G(E)"),
+                ("ads-v2.graph", "node E -> O {}\nnode O {}\ngraph G(E)"),
+            ],
+        );
+        let paths = graph_files(&dir).unwrap();
+        let (document, _) = load_all(&paths, true).unwrap();
+        let names: Vec<_> = document.mods.iter().map(|m| m.name.0.as_str()).collect();
+        assert_eq!(names, ["ads_v2", "search"]);
+        assert!(document.nodes.is_empty());
+        assert_eq!(document.mods[1].items.nodes.len(), 2);
+
+        let (document, sources) = load_all(&paths, false).unwrap();
+        assert!(document.mods.is_empty());
+        assert_eq!(document.nodes.len(), 4);
+        let errors = crate::resolver::Resolver::default()
+            .resolve_document(document)
+            .err()
+            .unwrap();
+        let report = sources.report(&errors[0]);
+        assert_eq!(report.message, "the name `E` is defined multiple times");
+        assert!(report.file.ends_with("search.graph"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_all_imports() {
+        let dir = write_files(
+            "load-all-imports",
+            &[
+                (
+                    "search.graph",
+                    "import \"common/o.graph\";\nnode E -> O {}\ngraph G(E)",
+                ),
+                (
+                    "ads.graph",
+                    "import \"common/o.graph\";\nnode E -> O {}\ngraph G(E)",
+                ),
+                ("common/o.graph", "node O {}"),
+            ],
+        );
+        let paths = graph_files(&dir).unwrap();
+        let (document, sources) = load_all(&paths, true).unwrap();
+        assert_eq!(sources.files().len(), 3);
+        assert_eq!(document.nodes.len(), 1);
+        assert_eq!(document.mods[0].items.nodes.len(), 1);
+        if let Err(errors) = crate::resolver::Resolver::default().resolve_document(document) {
+            panic!("{errors:?}");
+        }
+
+        std::fs::write(dir.join("ads.graph"), "import \"search.graph\";\nnode A {}").unwrap();
+        match load_all(&paths, true) {
+            Err(Error::Resolve(reports)) => {
+                assert!(reports[0]
+                    .message
+                    .ends_with("search.graph`, which is compiled into module `search`"));
+                assert!(reports[0].file.ends_with("ads.graph"));
+            }
+            other => panic!("{other:?}"),
+        }
+        load_all(&paths, false).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_module_name() {
+        let dir = write_files("module-name", &[("type.graph", "node A {}\ngraph G(A)")]);
+        match load_all(&[dir.join("type.graph")], true) {
+            Err(Error::Resolve(reports)) => {
+                assert!(reports[0].message.ends_with("type.graph`"));
+                assert_eq!(reports[0].notes, ["`type` is not a valid Rust module name"]);
+            }
+            other => panic!("{other:?}"),
+        }
+        load_all(&[dir.join("type.graph")], false).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_graph_files() {
+        let dir = write_files(
+            "graph-files",
+            &[
+                ("b.graph", ""),
+                ("a.graph", ""),
+                ("a.txt", ""),
+                ("nested/c.graph", ""),
+            ],
+        );
+        let names = |files: Vec<PathBuf>| -> Vec<String> {
+            files
+                .iter()
+                .map(|f| f.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(names(graph_files(&dir).unwrap()), ["a.graph", "b.graph"]);
+        assert_eq!(
+            names(graph_files(dir.join("a.*")).unwrap()),
+            ["a.graph", "a.txt"]
+        );
+        assert_eq!(
+            names(graph_files(dir.join("?.graph")).unwrap()),
+            ["a.graph", "b.graph"]
+        );
+        assert!(graph_files(dir.join("*.rs")).is_err());
+        assert_eq!(
+            graph_files(dir.join("b.graph")).unwrap(),
+            [dir.join("b.graph")]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.graph", "a.graph"));
+        assert!(wildcard_match("*.graph", ".graph"));
+        assert!(wildcard_match("a*b*c", "aXbYbc"));
+        assert!(wildcard_match("a?c", "abc"));
+        assert!(!wildcard_match("*.graph", "a.graph.bak"));
+        assert!(!wildcard_match("a?c", "ac"));
+    }
+
+    #[test]
+    fn test_import_cycle() {
+        let dir = write_files(
+            "cycle",
+            &[
+                ("a.graph", "import \"b.graph\";\nnode A {}"),
+                ("b.graph", "node B {}\nimport \"a.graph\";"),
+            ],
+        );
+        match load(dir.join("a.graph")) {
+            Err(Error::Resolve(reports)) => {
+                assert!(reports[0].message.ends_with("a.graph` imports itself"));
+                assert!(reports[0].file.ends_with("b.graph"));
+                assert_eq!(reports[0].line, 2);
+            }
+            other => panic!("{other:?}"),
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+    time::Duration,
+};
+
+use faststr::FastStr;
+
+#[derive(Default, Debug)]
+pub struct TypeMap(HashMap<TypeId, Box<dyn Any + Sync + Send>>);
+
+impl TypeMap {
+    pub fn insert<T: 'static + Sync + Send>(&mut self, v: T) {
+        self.0.insert(TypeId::of::<T>(), Box::new(v));
+    }
+
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.0
+            .get(&TypeId::of::<T>())
+            .map(|v| v.downcast_ref().unwrap())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.0.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn remove<T: 'static>(&mut self) {
+        self.0.remove(&TypeId::of::<T>());
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Tags(TypeMap);
+
+impl Deref for Tags {
+    type Target = TypeMap;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Tags {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Tags {
+    /// The value of the annotation `key` as written, whether or not this crate knows it.
+    pub fn raw(&self, key: &str) -> Option<&str> {
+        self.get::<Raw>()?
+            .0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Every annotation of a node, field or graph as key and value, in declaration order.
+#[derive(Clone, Default)]
+pub struct Raw(pub Vec<(FastStr, FastStr)>);
+
+#[derive(Clone)]
+pub struct Construct(pub FastStr);
+
+pub trait Annotation: FromStr {
+    const KEY: &'static str;
+}
+
+impl FromStr for Construct {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(FastStr::new(s)))
+    }
+}
+
+impl Annotation for Construct {
+    const KEY: &'static str = "default";
+}
+
+#[derive(Clone)]
+pub struct Editable(pub bool);
+
+impl FromStr for Editable {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s == "true"))
+    }
+}
+
+impl Annotation for Editable {
+    const KEY: &'static str = "editable";
+}
+
+/// How long a node may run, written as `#[timeout_ms = "50"]` on the node.
+#[derive(Clone)]
+pub struct Timeout(pub Duration);
+
+impl FromStr for Timeout {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(Duration::from_millis(s.parse()?)))
+    }
+}
+
+impl Annotation for Timeout {
+    const KEY: &'static str = "timeout_ms";
+}
+
+/// How many more times a node is run after it fails, written as `#[retry = "3"]`.
+#[derive(Clone)]
+pub struct Retry(pub u32);
+
+impl FromStr for Retry {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse()?))
+    }
+}
+
+impl Annotation for Retry {
+    const KEY: &'static str = "retry";
+}
+
+/// The wait before the first retry of a node, doubled for every retry after it.
+#[derive(Clone)]
+pub struct Backoff(pub Duration);
+
+impl FromStr for Backoff {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(Duration::from_millis(s.parse()?)))
+    }
+}
+
+impl Annotation for Backoff {
+    const KEY: &'static str = "backoff_ms";
+}
+
+/// How a node waits for its predecessors, written as `#[join = "first"]` on the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Join {
+    /// Run once every predecessor succeeded, which is the default.
+    All,
+    /// Run with whichever predecessor succeeds first.
+    First,
+    /// Run once this many predecessors succeeded, e.g. `#[join = "quorum(2)"]`.
+    Quorum(usize),
+}
+
+impl FromStr for Join {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Join::All),
+            "first" => Ok(Join::First),
+            _ => match s
+                .strip_prefix("quorum(")
+                .and_then(|s| s.strip_suffix(')'))
+                .map(|k| k.trim().parse())
+            {
+                Some(Ok(0)) => Err("a quorum must be at least 1".to_string()),
+                Some(Ok(k)) => Ok(Join::Quorum(k)),
+                Some(Err(e)) => Err(format!("invalid quorum: {e}")),
+                None => Err("expected `all`, `first` or `quorum(k)`".to_string()),
+            },
+        }
+    }
+}
+
+impl Annotation for Join {
+    const KEY: &'static str = "join";
+}
+
+/// How many items a node run for each item of a response works on at once, written as
+/// `#[concurrency = "8"]` on the node.
+#[derive(Clone, Copy)]
+pub struct Concurrency(pub usize);
+
+impl FromStr for Concurrency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(0) => Err("the concurrency must be at least 1".to_string()),
+            Ok(limit) => Ok(Self(limit)),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+impl Annotation for Concurrency {
+    const KEY: &'static str = "concurrency";
+}
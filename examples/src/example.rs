@@ -3,7 +3,8 @@ use std::{
     time::{Duration, Instant},
 };
 
-use gen_graph::{Runnable, E, G, O, X, Y};
+use gen_graph::{E, G, O, X, Y};
+use static_graph::Runnable;
 
 #[allow(warnings, clippy::all)]
 pub mod gen_graph {
@@ -78,6 +79,8 @@ impl Runnable<Request, EResponse> for Y {
     }
 }
 
+// Only read through `Debug` when `main` prints the response.
+#[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct OResponse(String);
 
@@ -0,0 +1,35 @@
+use gen_graph::{Greet, Shout, G};
+use static_graph::Runnable;
+
+#[allow(warnings, clippy::all)]
+pub mod gen_graph {
+    static_graph::graph! {
+        node Greet -> Shout: String {}
+        node Shout {}
+        graph G(Greet): String -> String, error void
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let resp = G::new().run("world".to_string()).await;
+    println!("resp is {resp:?}");
+}
+
+impl Runnable<String, ()> for Greet {
+    type Resp = String;
+    type Error = ();
+
+    async fn run(&self, req: String, _prev_resp: ()) -> Result<Self::Resp, Self::Error> {
+        Ok(format!("hello, {req}"))
+    }
+}
+
+impl Runnable<String, String> for Shout {
+    type Resp = String;
+    type Error = ();
+
+    async fn run(&self, _req: String, prev_resp: String) -> Result<Self::Resp, Self::Error> {
+        Ok(prev_resp.to_uppercase())
+    }
+}
@@ -1,6 +1,7 @@
 use std::time::{Duration, Instant};
 
-use gen_graph::{Runnable, E, G, O, Q, R, W, X, Y, Z};
+use gen_graph::{E, G, O, Q, R, W, X, Y, Z};
+use static_graph::Runnable;
 
 #[allow(warnings, clippy::all)]
 pub mod gen_graph {
@@ -19,11 +20,7 @@ impl Custom {
 #[tokio::main]
 async fn main() {
     let start = Instant::now();
-    let resp = G::new()
-        .run::<Request, EResponse, XResponse, YResponse, WResponse, ZResponse, QResponse, RResponse, OResponse, ()>(
-            Request
-        )
-        .await;
+    let resp = G::new().run(Request).await;
     let duration = start.elapsed();
 
     println!("Time elapsed is {duration:?}, resp is {resp:?}");
@@ -0,0 +1,164 @@
+//! The `graph!` macro, which generates the code of a graph description written inline, so
+//! small graphs need neither a `build.rs` nor `static_graph::include_graph!`. Use it as
+//! `static_graph::graph!` with the `macros` feature of `static-graph`.
+
+use proc_macro2::{Delimiter, Span, TokenStream, TokenTree};
+use static_graph_compiler::{diagnostic::Report, Error};
+
+/// Generate the code of the graph description given to the macro, as `build.rs` would
+/// generate it from a file:
+///
+/// ```ignore
+/// #[allow(warnings, clippy::all)]
+/// pub mod gen_graph {
+///     static_graph::graph! {
+///         node E -> O {}
+///         node O {}
+///         graph G(E)
+///     }
+/// }
+/// ```
+///
+/// Errors in the description are reported at the tokens they are found at. A description
+/// written inline cannot import other files.
+#[proc_macro]
+pub fn graph(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    expand(input.into()).into()
+}
+
+fn expand(input: TokenStream) -> TokenStream {
+    let source = Source::new(input);
+    match static_graph_compiler::compile_str(&source.text) {
+        Ok(stream) => stream,
+        Err(Error::Parse(report)) => source.error(&report),
+        Err(Error::Resolve(reports)) => reports.iter().map(|r| source.error(r)).collect(),
+        Err(e) => {
+            let message = e.to_string();
+            quote::quote!(::std::compile_error!(#message);)
+        }
+    }
+}
+
+/// The tokens given to the macro written out as a graph description, along with the span
+/// each token was written at.
+struct Source {
+    text: String,
+    /// The byte range of every token in `text`, in order.
+    tokens: Vec<(usize, usize, Span)>,
+}
+
+impl Source {
+    fn new(input: TokenStream) -> Self {
+        let mut source = Self {
+            text: String::new(),
+            tokens: Vec::new(),
+        };
+        source.push_stream(input);
+        source
+    }
+
+    fn push_stream(&mut self, input: TokenStream) {
+        // Tokens are written without space in between, the way the DSL is usually written
+        // around punctuation, except that keywords and names are kept apart.
+        let mut word = false;
+        for tree in input {
+            let is_word = matches!(tree, TokenTree::Ident(_) | TokenTree::Literal(_));
+            if word && is_word {
+                self.text.push(' ');
+            }
+            word = is_word;
+            match tree {
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => ("", ""),
+                    };
+                    self.push(open, group.span_open());
+                    self.push_stream(group.stream());
+                    self.push(close, group.span_close());
+                }
+                TokenTree::Punct(punct) => self.push(&punct.as_char().to_string(), punct.span()),
+                TokenTree::Ident(ident) => self.push(&ident.to_string(), ident.span()),
+                TokenTree::Literal(literal) => self.push(&literal.to_string(), literal.span()),
+            }
+        }
+    }
+
+    fn push(&mut self, token: &str, span: Span) {
+        let start = self.text.len();
+        self.text.push_str(token);
+        self.tokens.push((start, self.text.len(), span));
+    }
+
+    /// The token at `offset`, or the first one after it when `offset` falls between tokens.
+    fn token_at(&self, offset: usize) -> Option<&(usize, usize, Span)> {
+        self.tokens
+            .iter()
+            .find(|(_, end, _)| *end > offset)
+            .or(self.tokens.last())
+    }
+
+    /// A `compile_error!` reporting `report` at the token it points at.
+    fn error(&self, report: &Report) -> TokenStream {
+        let span = self
+            .token_at(report.span.start)
+            .map_or_else(Span::call_site, |(_, _, span)| *span);
+        let mut message = report.message.clone();
+        for note in &report.notes {
+            message.push_str("\nnote: ");
+            message.push_str(note);
+        }
+        quote::quote_spanned!(span=> ::std::compile_error!(#message);)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(src: &str) -> Source {
+        Source::new(src.parse().unwrap())
+    }
+
+    #[test]
+    fn test_source() {
+        let source = read(
+            r#"
+            node E -> (X, ?Y): crate::EResponse { x: map<string, list<i32>> }
+            #[retry = "1"]
+            node X -> O {}
+            node Y -> O {}
+            node O {}
+            graph G(E): crate::Request -> crate::OResponse
+            "#,
+        );
+        assert!(source.text.contains("node E->(X,?Y):crate::EResponse{"));
+        static_graph_compiler::compile_str(&source.text).unwrap();
+    }
+
+    #[test]
+    fn test_error_span() {
+        let source = read("node A -> Missing {}\ngraph G(A)");
+        match static_graph_compiler::compile_str(&source.text) {
+            Err(Error::Resolve(reports)) => {
+                let (start, end, _) = source.token_at(reports[0].span.start).unwrap();
+                assert_eq!(&source.text[*start..*end], "Missing");
+            }
+            other => panic!("{other:?}"),
+        }
+
+        let source = read("node A B {}");
+        match static_graph_compiler::compile_str(&source.text) {
+            Err(Error::Parse(report)) => {
+                let (start, end, _) = source.token_at(report.span.start).unwrap();
+                assert_eq!(&source.text[*start..*end], "B");
+            }
+            other => panic!("{other:?}"),
+        }
+        assert!(expand("node A B {}".parse().unwrap())
+            .to_string()
+            .contains("compile_error"));
+    }
+}
@@ -60,7 +60,7 @@
 //!     .unwrap();
 //! ```
 
-//! Finally, in `main.rs` write your own logic for your nodes in the graph. The generated code will be in the `OUT_DIR` directory by default, the graph name is `G`, and the nodes name are `E`, `X`, `Y`, `O`. You should implement the `Runnable` trait for each node, and then you can automatically run the graph in maximum parallel by calling `G::new().run()`.
+//! Finally, in `main.rs` write your own logic for your nodes in the graph. The generated code will be in the `OUT_DIR` directory by default, the graph name is `G`, and the nodes name are `E`, `X`, `Y`, `O`. You should implement the [`Runnable`] trait for each node, and then you can automatically run the graph in maximum parallel by calling `G::new().run()`.
 
 //! ```rust, ignore
 //! use std::{
@@ -68,7 +68,8 @@
 //!     time::{Duration, Instant},
 //! };
 
-//! use gen_graph::{Runnable, E, G, O, X, Y};
+//! use gen_graph::{E, G, O, X, Y};
+//! use static_graph::Runnable;
 
 //! #[allow(warnings, clippy::all)]
 //! pub mod gen_graph {
@@ -107,7 +108,7 @@
 //! #[derive(Clone)]
 //! pub struct EResponse(Duration);
 
-//! //! impl Runnable<Request, ()> for E {
+//! impl Runnable<Request, ()> for E {
 //!     type Resp = EResponse;
 //!     type Error = ();
 
@@ -120,7 +121,7 @@
 //! #[derive(Clone)]
 //! pub struct XResponse(bool);
 
-//! //! impl Runnable<Request, EResponse> for X {
+//! impl Runnable<Request, EResponse> for X {
 //!     type Resp = XResponse;
 //!     type Error = ();
 
@@ -133,7 +134,7 @@
 //! #[derive(Clone)]
 //! pub struct YResponse(bool);
 
-//! //! impl Runnable<Request, EResponse> for Y {
+//! impl Runnable<Request, EResponse> for Y {
 //!     type Resp = YResponse;
 //!     type Error = ();
 
@@ -146,7 +147,7 @@
 //! #[derive(Clone, Debug)]
 //! pub struct OResponse(String);
 
-//! //! impl Runnable<Request, (XResponse, YResponse)> for O {
+//! impl Runnable<Request, (XResponse, YResponse)> for O {
 //!     type Resp = OResponse;
 //!     type Error = ();
 
@@ -166,28 +167,21 @@
 //! }
 //! ```
 //!
-pub mod codegen;
-pub mod context;
-pub mod index;
-pub mod parser;
-pub mod resolver;
-pub mod symbol;
-pub mod tags;
+pub mod runnable;
 
 pub use arc_swap::*;
 pub use tokio::*;
 
-use crate::{
-    codegen::Codegen,
-    context::Context,
-    parser::{document::Document, Parser},
-    resolver::{ResolveResult, Resolver},
+pub use static_graph_compiler::{
+    codegen, compile_str, configure, context, diagnostic, error, index, parser, resolver, source,
+    symbol, tags, Builder, Compiled, Formatter,
 };
+#[cfg(feature = "macros")]
+pub use static_graph_macros::graph;
 
-use std::{
-    io::Write,
-    path::{Path, PathBuf},
-    process::{exit, Command},
+pub use crate::{
+    error::{Error, Result},
+    runnable::{GraphError, Route, Runnable},
 };
 
 #[macro_export]
@@ -196,123 +190,3 @@ macro_rules! include_graph {
         include!(concat!(env!("OUT_DIR"), concat!("/", $graph)));
     };
 }
-
-#[must_use]
-pub fn configure() -> Builder {
-    Builder {
-        emit_rerun_if_changed: std::env::var_os("CARGO").is_some(),
-        out_dir: None,
-        file_name: "gen_graph.rs".into(),
-        enable_mermaid: false,
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct Builder {
-    emit_rerun_if_changed: bool,
-    out_dir: Option<PathBuf>,
-    file_name: PathBuf,
-    enable_mermaid: bool, // generate mermaid file
-}
-
-impl Builder {
-    #[must_use]
-    pub fn out_dir(mut self, out_dir: impl AsRef<Path>) -> Self {
-        self.out_dir = Some(out_dir.as_ref().to_path_buf());
-        self
-    }
-
-    #[must_use]
-    pub fn file_name(mut self, file_name: impl AsRef<Path>) -> Self {
-        self.file_name = file_name.as_ref().to_path_buf();
-        self
-    }
-
-    #[must_use]
-    pub fn emit_rerun_if_changed(mut self, enable: bool) -> Self {
-        self.emit_rerun_if_changed = enable;
-        self
-    }
-
-    #[must_use]
-    pub fn enable_mermaid(mut self, enable: bool) -> Self {
-        self.enable_mermaid = enable;
-        self
-    }
-
-    pub fn compile(self, graph: impl AsRef<Path>) -> std::io::Result<()> {
-        let out_dir = if let Some(out_dir) = self.out_dir.as_ref() {
-            out_dir.clone()
-        } else {
-            PathBuf::from(std::env::var("OUT_DIR").unwrap())
-        };
-
-        if self.emit_rerun_if_changed {
-            println!("cargo:rerun-if-changed={}", graph.as_ref().display());
-        }
-
-        let input = unsafe { String::from_utf8_unchecked(std::fs::read(graph).unwrap()) };
-
-        let document = Document::parse(&input).unwrap().1;
-        let ResolveResult {
-            graphs,
-            nodes,
-            fields,
-            tags,
-            entrys,
-        } = Resolver::default().resolve_document(document);
-
-        let mut cx = Context::new();
-        cx.set_graphs(graphs);
-        cx.set_nodes(nodes);
-        cx.set_fields(fields);
-        cx.set_tags(tags);
-
-        let mut cg = Codegen::new(cx);
-
-        if self.enable_mermaid {
-            let ret = cg.mermaid(&entrys);
-            let mut name = self.file_name.file_stem().unwrap().to_os_string();
-            name.push(".mermaid");
-            let out = out_dir.join(name);
-            let mut file = std::io::BufWriter::new(std::fs::File::create(&out).unwrap());
-            file.write_all(ret.trim().as_bytes()).unwrap();
-            file.flush().unwrap();
-        }
-        let stream = cg.write_document(entrys);
-        let out = out_dir.join(self.file_name);
-        let mut file = std::io::BufWriter::new(std::fs::File::create(&out).unwrap());
-        file.write_all(stream.to_string().as_bytes()).unwrap();
-        file.flush().unwrap();
-        fmt_file(out);
-
-        Ok(())
-    }
-}
-
-fn fmt_file<P: AsRef<Path>>(file: P) {
-    let file = file.as_ref();
-    if let Some(a) = file.extension() {
-        if a != "rs" {
-            return;
-        }
-    };
-
-    let result = Command::new(std::env::var("RUSTFMT").unwrap_or_else(|_| "rustfmt".to_owned()))
-        .arg("--emit")
-        .arg("files")
-        .arg("--edition")
-        .arg("2021")
-        .arg(file)
-        .output();
-
-    match result {
-        Err(e) => eprintln!("{e:?}",),
-        Ok(output) => {
-            if !output.status.success() {
-                std::io::stderr().write_all(&output.stderr).unwrap();
-                exit(output.status.code().unwrap_or(1))
-            }
-        }
-    }
-}
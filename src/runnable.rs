@@ -0,0 +1,70 @@
+use std::{fmt, future::Future};
+
+use tokio::sync::broadcast::error::RecvError;
+
+/// A node of a graph.
+///
+/// `Req` is the request the graph was run with and `PrevResp` is the response of the
+/// predecessor, or a tuple of the responses of all predecessors in declaration order.
+/// The code generated for every graph drives its nodes through this trait, so one node
+/// type can be shared by graphs from different files and wrapped by generic middleware.
+pub trait Runnable<Req, PrevResp> {
+    type Resp;
+    type Error;
+
+    fn run(
+        &self,
+        req: Req,
+        prev_resp: PrevResp,
+    ) -> impl Future<Output = Result<Self::Resp, Self::Error>> + Send;
+}
+
+/// Implemented by the `{Switch}Route` enum generated for every switch node, whose variants
+/// name the branch to run and carry the payload that branch receives.
+pub trait Route {
+    type Payload;
+}
+
+/// Why a run of a generated graph failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError<E> {
+    /// A node returned an error; nodes depending on it were not run.
+    Node(E),
+    /// A node went away without passing on its response, e.g. because its task panicked.
+    Recv(RecvError),
+    /// A node ran longer than its `timeout_ms` annotation allows.
+    Timeout { node: &'static str },
+    /// A node annotated with `retry` failed on every attempt.
+    Exhausted {
+        node: &'static str,
+        attempts: u32,
+        /// Why the last attempt failed.
+        last: Box<GraphError<E>>,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for GraphError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::Node(e) => write!(f, "node failed: {e}"),
+            GraphError::Recv(e) => write!(f, "node response lost: {e}"),
+            GraphError::Timeout { node } => write!(f, "node `{node}` timed out"),
+            GraphError::Exhausted {
+                node,
+                attempts,
+                last,
+            } => write!(f, "node `{node}` failed after {attempts} attempts: {last}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for GraphError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GraphError::Node(e) => Some(e),
+            GraphError::Recv(e) => Some(e),
+            GraphError::Timeout { .. } => None,
+            GraphError::Exhausted { last, .. } => Some(&**last),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+#[macro_use]
+mod common;
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use common::{Failed, Num};
+use static_graph::GraphError;
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> (Fail, Slow): crate::Num {}
+        node Fail -> O: crate::Num {}
+        node Slow -> O: crate::Num {}
+        node O: crate::Num {}
+        graph G(E): crate::Num -> crate::Num, error crate::Failed
+
+        node Start -> Sleepy: crate::Num {}
+        node Sleepy -> End: crate::Num {}
+        node End: crate::Num {}
+        graph H(Start): crate::Num -> crate::Num, error crate::Failed
+    }
+}
+
+/// Set by `Slow` and `Sleepy` once they finish sleeping, which they never should.
+static SLOW_DONE: AtomicBool = AtomicBool::new(false);
+static SLEEPY_DONE: AtomicBool = AtomicBool::new(false);
+
+entry!(gen::E);
+runnable!(gen::Fail, Num => Num, |_, _, _| { Err(Failed) });
+runnable!(gen::Slow, Num => Num, |_, _, prev| {
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    SLOW_DONE.store(true, Ordering::SeqCst);
+    Ok(prev)
+});
+runnable!(gen::O, (Num, Num) => Num, |_, _, (a, b)| { Ok(a + b) });
+
+entry!(gen::Start);
+runnable!(gen::Sleepy, Num => Num, |_, _, prev| {
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    SLEEPY_DONE.store(true, Ordering::SeqCst);
+    Ok(prev)
+});
+forward!(gen::End);
+
+#[tokio::test]
+async fn test_failed_run_aborts_siblings() {
+    let resp = gen::G::new().run(1).await;
+    assert!(matches!(resp, Err(GraphError::Node(Failed))), "{resp:?}");
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(!SLOW_DONE.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn test_dropped_run_aborts_nodes() {
+    let graph = gen::H::new();
+    let resp = tokio::time::timeout(Duration::from_millis(50), graph.run(1)).await;
+    assert!(resp.is_err());
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(!SLEEPY_DONE.load(Ordering::SeqCst));
+}
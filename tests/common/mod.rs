@@ -0,0 +1,55 @@
+//! Types and helpers shared by the tests running generated graphs.
+#![allow(dead_code, unused_macros)]
+
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+
+/// The request of the graphs under test, and the response of most of their nodes.
+pub type Num = u64;
+
+/// A node field recording that the node got somewhere.
+pub type Flag = AtomicBool;
+
+/// A node field counting something about the node, such as how often it ran.
+pub type Counter = AtomicUsize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failed;
+
+/// Implement `Runnable<Num, $prev_ty>` for `$node`, responding with `$resp` or `Failed`, as
+/// `$body` run with the node, the request and the responses of its predecessors bound to the
+/// given patterns.
+macro_rules! runnable {
+    ($node:ty, $prev_ty:ty => $resp:ty, |$this:pat_param, $req:pat_param, $prev:pat_param| $body:block) => {
+        impl ::static_graph::Runnable<$crate::common::Num, $prev_ty> for $node {
+            type Resp = $resp;
+            type Error = $crate::common::Failed;
+
+            async fn run(
+                &self,
+                $req: $crate::common::Num,
+                $prev: $prev_ty,
+            ) -> ::std::result::Result<$resp, $crate::common::Failed> {
+                let $this = self;
+                $body
+            }
+        }
+    };
+}
+
+/// Implement the entry node `$node`, which responds with the request.
+macro_rules! entry {
+    ($node:ty) => {
+        runnable!($node, () => $crate::common::Num, |_, req, _| { Ok(req) });
+    };
+}
+
+/// Implement `$node`, which responds with the response of its predecessors, of type `$ty`
+/// or else `Num`.
+macro_rules! forward {
+    ($node:ty) => {
+        forward!($node, $crate::common::Num);
+    };
+    ($node:ty, $ty:ty) => {
+        runnable!($node, $ty => $ty, |_, _, prev| { Ok(prev) });
+    };
+}
@@ -0,0 +1,34 @@
+#[macro_use]
+mod common;
+
+use common::{Failed, Num};
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> (X if crate::is_even, Y) {}
+        node X -> O: crate::Num {}
+        node Y -> O: crate::Num {}
+        node O: crate::Out {}
+        graph G(E): crate::Num -> crate::Out, error crate::Failed
+    }
+}
+
+pub type Out = (Option<Num>, Num);
+
+pub fn is_even(req: &Num, resp: &Num) -> bool {
+    assert_eq!(req, resp);
+    req.is_multiple_of(2)
+}
+
+entry!(gen::E);
+runnable!(gen::X, Num => Num, |_, _, prev| { Ok(prev * 10) });
+runnable!(gen::Y, Num => Num, |_, _, prev| { Ok(prev + 1) });
+forward!(gen::O, Out);
+
+#[tokio::test]
+async fn test_conditional_edge() {
+    let graph = gen::G::new();
+    assert_eq!(graph.run(2).await, Ok((Some(20), 3)));
+    assert_eq!(graph.run(3).await, Ok((None, 4)));
+}
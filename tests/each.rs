@@ -0,0 +1,54 @@
+#[macro_use]
+mod common;
+
+use std::{sync::atomic::Ordering, time::Duration};
+
+use common::{Counter, Failed, Num};
+use static_graph::GraphError;
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> each S: crate::Items {}
+        #[concurrency = "2"]
+        node S -> R: crate::Num {
+            running: crate::Counter,
+            max_running: crate::Counter,
+        }
+        node R: crate::Items {}
+        graph G(E): crate::Num -> crate::Items, error crate::Failed
+    }
+}
+
+pub type Items = Vec<Num>;
+
+runnable!(gen::E, () => Items, |_, req, _| { Ok((0..req).collect()) });
+// Fails on item 100; later items finish sooner.
+runnable!(gen::S, Num => Num, |s, _, item| {
+    let running = s.running.fetch_add(1, Ordering::SeqCst) + 1;
+    s.max_running.fetch_max(running, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(50 - item.min(10) * 5)).await;
+    s.running.fetch_sub(1, Ordering::SeqCst);
+    if item == 100 {
+        Err(Failed)
+    } else {
+        Ok(item * 10)
+    }
+});
+forward!(gen::R, Items);
+
+#[tokio::test]
+async fn test_each() {
+    let graph = gen::G::new();
+    assert_eq!(graph.run(8).await, Ok(vec![0, 10, 20, 30, 40, 50, 60, 70]));
+    assert_eq!(graph.e.s.max_running.load(Ordering::SeqCst), 2);
+
+    assert_eq!(graph.run(0).await, Ok(vec![]));
+}
+
+#[tokio::test]
+async fn test_each_failed() {
+    let graph = gen::G::new();
+    let resp = graph.run(101).await;
+    assert!(matches!(resp, Err(GraphError::Node(Failed))), "{resp:?}");
+}
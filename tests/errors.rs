@@ -0,0 +1,35 @@
+#[macro_use]
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{Failed, Num};
+use static_graph::GraphError;
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> (Fail, Slow): crate::Num {}
+        node Fail -> O: crate::Num {}
+        node Slow -> O: crate::Num {}
+        node O: crate::Num {}
+        graph G(E): crate::Num -> crate::Num, error crate::Failed
+    }
+}
+
+entry!(gen::E);
+runnable!(gen::Fail, Num => Num, |_, _, _| { Err(Failed) });
+runnable!(gen::Slow, Num => Num, |_, _, prev| {
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    Ok(prev)
+});
+runnable!(gen::O, (Num, Num) => Num, |_, _, (a, b)| { Ok(a + b) });
+
+#[tokio::test]
+async fn test_node_error_ends_run() {
+    let start = Instant::now();
+    let resp = gen::G::new().run(1).await;
+    assert!(matches!(resp, Err(GraphError::Node(Failed))), "{resp:?}");
+    // The run does not wait for the branch still sleeping.
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,98 @@
+#[macro_use]
+mod common;
+
+use std::{sync::atomic::Ordering, time::Duration};
+
+use common::{Failed, Flag, Num};
+use static_graph::GraphError;
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> (A, B, C): crate::Num {}
+        node A -> O: crate::Num {}
+        node B -> O: crate::Num {
+            done: crate::Flag,
+        }
+        node C -> (O, Z): crate::Num {
+            done: crate::Flag,
+        }
+        #[join = "first"]
+        node O -> Z: crate::Num {}
+        node Z: crate::Num {}
+        graph G(E): crate::Num -> crate::Num, error crate::Failed
+
+        node F -> (X, Y): crate::Num {}
+        node X -> P: crate::Num {}
+        node Y -> P: crate::Num {}
+        #[join = "first"]
+        node P: crate::Num {}
+        graph H(F): crate::Num -> crate::Num, error crate::Failed
+    }
+}
+
+entry!(gen::E);
+// The bits of the request tell which of `A` and `B` fail; `A` answers first, then `B`,
+// then `C`.
+runnable!(gen::A, Num => Num, |_, req, _| {
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    if req & 1 == 0 {
+        Ok(1)
+    } else {
+        Err(Failed)
+    }
+});
+runnable!(gen::B, Num => Num, |b, req, _| {
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    if req & 2 == 0 {
+        b.done.store(true, Ordering::SeqCst);
+        Ok(2)
+    } else {
+        Err(Failed)
+    }
+});
+runnable!(gen::C, Num => Num, |c, _, _| {
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    c.done.store(true, Ordering::SeqCst);
+    Ok(3)
+});
+runnable!(gen::O, gen::OInput<Num, Num, Num> => Num, |_, _, input| {
+    match input {
+        gen::OInput::A(resp) | gen::OInput::B(resp) | gen::OInput::C(resp) => Ok(resp * 10),
+    }
+});
+runnable!(gen::Z, (Num, Num) => Num, |_, _, (o, c)| { Ok(o + c) });
+
+entry!(gen::F);
+runnable!(gen::X, Num => Num, |_, _, _| { Err(Failed) });
+runnable!(gen::Y, Num => Num, |_, _, _| { Err(Failed) });
+runnable!(gen::P, gen::PInput<Num, Num> => Num, |_, _, _| { Ok(0) });
+
+#[tokio::test]
+async fn test_first() {
+    let graph = gen::G::new();
+    assert_eq!(graph.run(0).await, Ok(13));
+
+    // `B` lost the race to `A` and was aborted, as `O` is its only consumer, while `C` ran
+    // on for `Z`.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(!graph.e.b.done.load(Ordering::SeqCst));
+    assert!(graph.e.c.done.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn test_first_after_failures() {
+    let graph = gen::G::new();
+    assert_eq!(graph.run(1).await, Ok(23));
+    assert!(graph.e.b.done.load(Ordering::SeqCst));
+
+    let graph = gen::G::new();
+    assert_eq!(graph.run(3).await, Ok(33));
+}
+
+#[tokio::test]
+async fn test_first_all_failed() {
+    let graph = gen::H::new();
+    let resp = graph.run(0).await;
+    assert!(matches!(resp, Err(GraphError::Node(Failed))), "{resp:?}");
+}
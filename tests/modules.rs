@@ -0,0 +1,68 @@
+#[macro_use]
+mod common;
+
+use std::sync::atomic::Ordering;
+
+use common::{Counter, Failed, Num};
+
+// Nodes sharing a name in different modules, referred to across modules.
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> (recall::A, rank::A): crate::Num {}
+        mod recall {
+            node A -> inner::B: crate::Num {}
+            mod inner {
+                node B -> O: crate::Num {
+                    calls: crate::Counter,
+                }
+            }
+            node C -> M: crate::Num {}
+        }
+        mod rank {
+            node A -> O: crate::Num {}
+            node C -> M: crate::Num {}
+        }
+        node O -> S: crate::Num {}
+        switch S -> (recall::C, rank::C): crate::Num {}
+        node M: crate::Num {}
+        mod search {
+            graph G(E): crate::Num -> crate::Num, error crate::Failed
+        }
+    }
+}
+
+entry!(gen::E);
+runnable!(gen::recall::A, Num => Num, |_, _, req| { Ok(req + 1) });
+runnable!(gen::recall::inner::B, Num => Num, |b, _, resp| {
+    b.calls.fetch_add(1, Ordering::SeqCst);
+    Ok(resp * 10)
+});
+runnable!(gen::rank::A, Num => Num, |_, _, req| { Ok(req + 2) });
+runnable!(gen::O, (Num, Num) => Num, |_, _, (recall, rank)| { Ok(recall + rank) });
+runnable!(gen::S, Num => gen::SRoute<Num>, |_, _, sum| {
+    Ok(if sum.is_multiple_of(2) {
+        gen::SRoute::RecallC(sum)
+    } else {
+        gen::SRoute::RankC(sum)
+    })
+});
+runnable!(gen::recall::C, Num => Num, |_, _, sum| { Ok(sum * 100) });
+runnable!(gen::rank::C, Num => Num, |_, _, sum| { Ok(sum * 1000) });
+runnable!(gen::M, gen::MInput<Num, Num> => Num, |_, _, input| {
+    match input {
+        gen::MInput::RecallC(resp) | gen::MInput::RankC(resp) => Ok(resp),
+    }
+});
+
+#[tokio::test]
+async fn test_modules() {
+    let graph = gen::search::G::new();
+    // (1 + 1) * 10 + 1 + 2 is odd, (2 + 1) * 10 + 2 + 2 is even.
+    assert_eq!(graph.run(1).await, Ok(23000));
+    assert_eq!(graph.run(2).await, Ok(3400));
+    assert_eq!(
+        graph.e.recall_a.recall_inner_b.calls.load(Ordering::SeqCst),
+        2
+    );
+}
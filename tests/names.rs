@@ -0,0 +1,61 @@
+#[macro_use]
+mod common;
+
+use common::{Failed, Num};
+
+// Nodes named after the locals of the generated `run`.
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node Req -> each Item: crate::Items {}
+        #[concurrency = "2"]
+        node Item -> Items: crate::Num {}
+        node Items -> (Tasks, Succeeded, Attempts): crate::Num {}
+        #[retry = "1", backoff_ms = "1"]
+        node Tasks -> Last: crate::Num {}
+        node Succeeded -> Last: crate::Num {}
+        node Attempts -> Last: crate::Num {}
+        #[join = "quorum(2)"]
+        node Last -> Payload: crate::Num {}
+        switch Payload -> (Index, Semaphore): crate::Num {}
+        node Index -> Backoff: crate::Num {}
+        node Semaphore -> Backoff: crate::Num {}
+        node Backoff: crate::Num {}
+        graph G(Req): crate::Num -> crate::Num, error crate::Failed
+    }
+}
+
+pub type Items = Vec<Num>;
+type Quorum = (Option<Num>, Option<Num>, Option<Num>);
+
+runnable!(gen::Req, () => Items, |_, req, _| { Ok((1..=req).collect()) });
+forward!(gen::Item);
+runnable!(gen::Items, Items => Num, |_, _, items| { Ok(items.iter().sum()) });
+forward!(gen::Tasks);
+forward!(gen::Succeeded);
+forward!(gen::Attempts);
+runnable!(gen::Last, Quorum => Num, |_, _, resps| {
+    Ok(resps.0.or(resps.1).or(resps.2).unwrap())
+});
+runnable!(gen::Payload, Num => gen::PayloadRoute<Num>, |_, _, sum| {
+    Ok(if sum.is_multiple_of(2) {
+        gen::PayloadRoute::Index(sum)
+    } else {
+        gen::PayloadRoute::Semaphore(sum)
+    })
+});
+runnable!(gen::Index, Num => Num, |_, _, sum| { Ok(sum * 10) });
+runnable!(gen::Semaphore, Num => Num, |_, _, sum| { Ok(sum * 100) });
+runnable!(gen::Backoff, gen::BackoffInput<Num, Num> => Num, |_, _, input| {
+    match input {
+        gen::BackoffInput::Index(resp) | gen::BackoffInput::Semaphore(resp) => Ok(resp),
+    }
+});
+
+#[tokio::test]
+async fn test_node_names_clashing_with_locals() {
+    let graph = gen::G::new();
+    // 1 + 2 + 3 is even, 1 + 2 is odd.
+    assert_eq!(graph.run(3).await, Ok(60));
+    assert_eq!(graph.run(2).await, Ok(300));
+}
@@ -0,0 +1,71 @@
+#[macro_use]
+mod common;
+
+use std::{sync::atomic::Ordering, time::Duration};
+
+use common::{Failed, Flag, Num};
+use static_graph::GraphError;
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> (A, B, C): crate::Num {}
+        node A -> O: crate::Num {}
+        node B -> O: crate::Num {}
+        node C -> O: crate::Num {
+            done: crate::Flag,
+        }
+        #[join = "quorum(2)"]
+        node O: crate::Out {}
+        graph G(E): crate::Num -> crate::Out, error crate::Failed
+    }
+}
+
+pub type Out = (Option<Num>, Option<Num>, Option<Num>);
+
+entry!(gen::E);
+// The bits of the request tell which of `A` and `B` fail.
+runnable!(gen::A, Num => Num, |_, req, _| {
+    if req & 1 == 0 {
+        Ok(1)
+    } else {
+        Err(Failed)
+    }
+});
+runnable!(gen::B, Num => Num, |_, req, _| {
+    if req & 2 == 0 {
+        Ok(2)
+    } else {
+        Err(Failed)
+    }
+});
+runnable!(gen::C, Num => Num, |c, _, _| {
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    c.done.store(true, Ordering::SeqCst);
+    Ok(3)
+});
+forward!(gen::O, Out);
+
+#[tokio::test]
+async fn test_quorum() {
+    let graph = gen::G::new();
+    assert_eq!(graph.run(0).await, Ok((Some(1), Some(2), None)));
+
+    // `C` was aborted once `A` and `B` reached the quorum.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(!graph.e.c.done.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn test_quorum_after_failure() {
+    let graph = gen::G::new();
+    assert_eq!(graph.run(1).await, Ok((None, Some(2), Some(3))));
+    assert!(graph.e.c.done.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn test_quorum_not_reached() {
+    let graph = gen::G::new();
+    let resp = graph.run(3).await;
+    assert!(matches!(resp, Err(GraphError::Node(Failed))), "{resp:?}");
+}
@@ -0,0 +1,60 @@
+#[macro_use]
+mod common;
+
+use std::sync::atomic::Ordering;
+
+use common::{Counter, Failed, Num};
+use static_graph::GraphError;
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> Flaky: crate::Num {}
+        #[retry = "3", backoff_ms = "1"]
+        node Flaky -> O: crate::Num {
+            calls: crate::Counter,
+        }
+        node O: crate::Num {}
+        graph G(E): crate::Num -> crate::Num, error crate::Failed
+    }
+}
+
+entry!(gen::E);
+// Fails the first `req` times it is called.
+runnable!(gen::Flaky, Num => Num, |flaky, req, prev| {
+    let calls = flaky.calls.fetch_add(1, Ordering::SeqCst) + 1;
+    if calls as Num <= req {
+        Err(Failed)
+    } else {
+        Ok(prev)
+    }
+});
+forward!(gen::O);
+
+fn calls(graph: &gen::G) -> usize {
+    graph.e.flaky.calls.load(Ordering::SeqCst)
+}
+
+#[tokio::test]
+async fn test_retry() {
+    for failures in 0..=3 {
+        let graph = gen::G::new();
+        assert_eq!(graph.run(failures).await.unwrap(), failures);
+        assert_eq!(calls(&graph), failures as usize + 1);
+    }
+}
+
+#[tokio::test]
+async fn test_retry_exhausted() {
+    let graph = gen::G::new();
+    let resp = graph.run(4).await;
+    assert!(
+        matches!(
+            &resp,
+            Err(GraphError::Exhausted { node: "Flaky", attempts: 4, last })
+                if matches!(**last, GraphError::Node(Failed))
+        ),
+        "{resp:?}"
+    );
+    assert_eq!(calls(&graph), 4);
+}
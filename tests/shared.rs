@@ -0,0 +1,58 @@
+#[macro_use]
+mod common;
+
+use std::sync::atomic::Ordering;
+
+use common::{Counter, Failed, Num};
+
+// Graphs sharing nodes, one of them entering through a node of another graph, and two of
+// them sharing their entry node.
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node A -> B: crate::Num {}
+        node C -> B: crate::Num {}
+        node B -> O: crate::Num {
+            calls: crate::Counter,
+        }
+        node O: crate::Num {}
+        graph Search(A): crate::Num -> crate::Num, error crate::Failed
+        graph Recommend(C): crate::Num -> crate::Num, error crate::Failed
+        graph Rank(A): crate::Num -> crate::Num, error crate::Failed
+        graph Tail(B): crate::Num -> crate::Num, error crate::Failed
+    }
+}
+
+runnable!(gen::A, () => Num, |_, req, _| { Ok(req + 1) });
+runnable!(gen::C, () => Num, |_, req, _| { Ok(req + 2) });
+runnable!(gen::B, Num => Num, |b, _, resp| {
+    b.calls.fetch_add(1, Ordering::SeqCst);
+    Ok(resp * 10)
+});
+// As the entry of `Tail`, `B` runs on the request alone.
+runnable!(gen::B, () => Num, |_, req, _| { Ok(req * 10) });
+forward!(gen::O);
+
+#[tokio::test]
+async fn test_shared_nodes() {
+    let search = gen::Search::new();
+    assert_eq!(search.run(1).await, Ok(20));
+    assert_eq!(search.a.b.calls.load(Ordering::SeqCst), 1);
+
+    let recommend = gen::Recommend::new();
+    assert_eq!(recommend.run(1).await, Ok(30));
+    assert_eq!(recommend.c.b.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_shared_entry() {
+    let rank = gen::Rank::new();
+    assert_eq!(rank.run(2).await, Ok(30));
+    assert_eq!(gen::Search::new().run(2).await, Ok(30));
+}
+
+#[tokio::test]
+async fn test_entry_inside_another_graph() {
+    let tail = gen::Tail::new();
+    assert_eq!(tail.run(4).await, Ok(40));
+}
@@ -0,0 +1,35 @@
+#[macro_use]
+mod common;
+
+use common::{Failed, Num};
+use static_graph::GraphError;
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> (P, R): crate::Num {}
+        node P -> ?R: crate::Num {}
+        node R: crate::Out {}
+        graph G(E): crate::Num -> crate::Out, error crate::Failed
+    }
+}
+
+pub type Out = (Num, Result<Num, GraphError<Failed>>);
+
+entry!(gen::E);
+runnable!(gen::P, Num => Num, |_, req, prev| {
+    if req == 0 {
+        Err(Failed)
+    } else {
+        Ok(prev + 1)
+    }
+});
+forward!(gen::R, Out);
+
+#[tokio::test]
+async fn test_soft_edge() {
+    let graph = gen::G::new();
+    assert_eq!(graph.run(1).await, Ok((1, Ok(2))));
+    // `R` still runs when `P` fails, and receives its error.
+    assert_eq!(graph.run(0).await, Ok((0, Err(GraphError::Node(Failed)))));
+}
@@ -0,0 +1,66 @@
+#[macro_use]
+mod common;
+
+use std::sync::atomic::Ordering;
+
+use common::{Counter, Failed, Num};
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node Load -> Fetch: crate::Num {}
+        node Fetch: crate::Num {
+            calls: crate::Counter,
+        }
+        graph Profile(Load): crate::Num -> crate::Num, error crate::Failed
+
+        use Profile -> Rank;
+        node E -> use Profile: crate::Num {}
+        node Rank: crate::Num {}
+        graph G(E): crate::Num -> crate::Num, error crate::Failed
+
+        // Two graphs running `Profile` and continuing to different nodes.
+        node SearchE -> use Profile -> SearchRank: crate::Num {}
+        node SearchRank: crate::Num {}
+        graph Search(SearchE): crate::Num -> crate::Num, error crate::Failed
+        node AdsE -> use Profile -> (AdsRank): crate::Num {}
+        node AdsRank: crate::Num {}
+        graph Ads(AdsE): crate::Num -> crate::Num, error crate::Failed
+    }
+}
+
+runnable!(gen::Load, () => Num, |_, req, _| { Ok(req + 1) });
+runnable!(gen::Fetch, Num => Num, |fetch, _, prev| {
+    fetch.calls.fetch_add(1, Ordering::SeqCst);
+    Ok(prev * 10)
+});
+entry!(gen::E);
+runnable!(gen::Rank, Num => Num, |_, _, prev| { Ok(prev + 1) });
+entry!(gen::SearchE);
+runnable!(gen::SearchRank, Num => Num, |_, _, prev| { Ok(prev + 2) });
+entry!(gen::AdsE);
+runnable!(gen::AdsRank, Num => Num, |_, _, prev| { Ok(prev + 3) });
+
+#[tokio::test]
+async fn test_subgraph() {
+    let graph = gen::G::new();
+    assert_eq!(graph.run(1).await, Ok(21));
+    assert_eq!(graph.run(2).await, Ok(31));
+
+    // Every run goes through the same instance of `Profile` and of its nodes.
+    let profile = &graph.e.use_profile.graph;
+    assert_eq!(profile.load.fetch.calls.load(Ordering::SeqCst), 2);
+    assert_eq!(profile.run(3).await, Ok(40));
+    assert_eq!(profile.load.fetch.calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_subgraph_use_sites() {
+    assert_eq!(gen::Search::new().run(1).await, Ok(22));
+    assert_eq!(gen::Ads::new().run(1).await, Ok(23));
+
+    let search = gen::Search::new();
+    search.run(1).await.unwrap();
+    let profile = &search.search_e.use_profile_after_search_e.graph;
+    assert_eq!(profile.load.fetch.calls.load(Ordering::SeqCst), 1);
+}
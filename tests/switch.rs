@@ -0,0 +1,104 @@
+#[macro_use]
+mod common;
+
+use std::sync::atomic::Ordering;
+
+use common::{Counter, Failed, Num};
+use static_graph::GraphError;
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> S: crate::Num {}
+        switch S -> (Hit, Miss): crate::Num {}
+        node Hit -> M: crate::Num {
+            calls: crate::Counter,
+        }
+        node Miss -> M: crate::Num {
+            calls: crate::Counter,
+        }
+        node M: crate::Num {}
+        graph G(E): crate::Num -> crate::Num, error crate::Failed
+    }
+}
+
+// The same graph, declaring no types, so that its `run` is generic.
+#[allow(warnings, clippy::all)]
+mod untyped {
+    static_graph::graph! {
+        node E -> S {}
+        switch S -> (Hit, Miss) {}
+        node Hit -> M {}
+        node Miss -> M {}
+        node M {}
+        graph G(E)
+    }
+}
+
+entry!(gen::E);
+// Even requests hit, odd ones miss, and a request of 0 fails.
+runnable!(gen::S, Num => gen::SRoute<Num>, |_, _, req| {
+    match req {
+        0 => Err(Failed),
+        req if req.is_multiple_of(2) => Ok(gen::SRoute::Hit(req)),
+        req => Ok(gen::SRoute::Miss(req)),
+    }
+});
+runnable!(gen::Hit, Num => Num, |hit, _, req| {
+    hit.calls.fetch_add(1, Ordering::SeqCst);
+    Ok(req * 10)
+});
+runnable!(gen::Miss, Num => Num, |miss, _, req| {
+    miss.calls.fetch_add(1, Ordering::SeqCst);
+    Ok(req * 100)
+});
+runnable!(gen::M, gen::MInput<Num, Num> => Num, |_, _, input| {
+    match input {
+        gen::MInput::Hit(resp) => Ok(resp + 1),
+        gen::MInput::Miss(resp) => Ok(resp + 2),
+    }
+});
+
+entry!(untyped::E);
+runnable!(untyped::S, Num => untyped::SRoute<Num>, |_, _, req| {
+    Ok(if req.is_multiple_of(2) {
+        untyped::SRoute::Hit(req)
+    } else {
+        untyped::SRoute::Miss(req)
+    })
+});
+forward!(untyped::Hit);
+runnable!(untyped::Miss, Num => Num, |_, _, req| { Ok(req * 100) });
+runnable!(untyped::M, untyped::MInput<Num, Num> => Num, |_, _, input| {
+    match input {
+        untyped::MInput::Hit(resp) | untyped::MInput::Miss(resp) => Ok(resp),
+    }
+});
+
+#[tokio::test]
+async fn test_switch() {
+    let graph = gen::G::new();
+    assert_eq!(graph.run(2).await, Ok(21));
+    assert_eq!(graph.e.s.hit.calls.load(Ordering::SeqCst), 1);
+    assert_eq!(graph.e.s.miss.calls.load(Ordering::SeqCst), 0);
+
+    assert_eq!(graph.run(3).await, Ok(302));
+    assert_eq!(graph.e.s.hit.calls.load(Ordering::SeqCst), 1);
+    assert_eq!(graph.e.s.miss.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_switch_error() {
+    let graph = gen::G::new();
+    let resp = graph.run(0).await;
+    assert!(matches!(resp, Err(GraphError::Node(Failed))), "{resp:?}");
+    assert_eq!(graph.e.s.hit.calls.load(Ordering::SeqCst), 0);
+    assert_eq!(graph.e.s.miss.calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_switch_generic_run() {
+    let graph = untyped::G::new();
+    assert_eq!(graph.run(4).await, Ok(4));
+    assert_eq!(graph.run(5).await, Ok(500));
+}
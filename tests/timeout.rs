@@ -0,0 +1,39 @@
+#[macro_use]
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{Failed, Num};
+use static_graph::GraphError;
+
+#[allow(warnings, clippy::all)]
+mod gen {
+    static_graph::graph! {
+        node E -> Slow: crate::Num {}
+        #[timeout_ms = "50"]
+        node Slow -> O: crate::Num {}
+        node O: crate::Num {}
+        graph G(E): crate::Num -> crate::Num, error crate::Failed
+    }
+}
+
+entry!(gen::E);
+// Sleeps for as many milliseconds as the request says.
+runnable!(gen::Slow, Num => Num, |_, req, prev| {
+    tokio::time::sleep(Duration::from_millis(req)).await;
+    Ok(prev)
+});
+forward!(gen::O);
+
+#[tokio::test]
+async fn test_timeout() {
+    assert_eq!(gen::G::new().run(1).await.unwrap(), 1);
+
+    let start = Instant::now();
+    let resp = gen::G::new().run(5000).await;
+    assert!(
+        matches!(resp, Err(GraphError::Timeout { node: "Slow" })),
+        "{resp:?}"
+    );
+    assert!(start.elapsed() < Duration::from_secs(2));
+}